@@ -55,7 +55,12 @@ pub fn ui_example(
             .show(ctx, |ui| {
                 ui.heading("Score");
                 if let Some(score) = score {
-                    ui.heading(score.0.to_string());
+                    ui.heading(score.total().to_string());
+                    ui.label(format!("Line clears: {}", score.line_clears));
+                    ui.label(format!("Combos: {}", score.combos));
+                    ui.label(format!("Back-to-back: {}", score.back_to_back));
+                    ui.label(format!("Drops: {}", score.drops));
+                    ui.label(format!("Perfect clears: {}", score.perfect_clears));
                 } else {
                     ui.heading("no score");
                 }
@@ -116,6 +121,9 @@ pub fn ui_example(
                         TileSize::Fixed(*tile_size)
                     };
                     ui.add(egui::Slider::new(&mut *tile_size, 10.0..=50.0).text("tile_size_max"));
+
+                    ui.separator();
+                    ui.checkbox(&mut options.ghost_piece, "Ghost Piece");
                 });
 
                 ui.with_layout(egui::Layout::bottom_up(egui::Align::Center), |ui| {
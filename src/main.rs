@@ -36,7 +36,9 @@ fn main() {
         meta_check: AssetMetaCheck::Never,
         ..default()
     }));
-    app.add_plugins(TetrisPlugin);
+    app.add_plugins(TetrisPlugin {
+        rules: rules_from_args(),
+    });
     // Dont change order above this comment
     app.add_systems(Startup, (camera_setup, audio::setup_audio));
     app.add_systems(Startup, setup_board_assets);
@@ -69,7 +71,10 @@ fn main() {
     {
         app.add_systems(OnEnter(AppState::Menu), menu::setup_menu)
             .add_systems(Update, menu::menu)
-            .add_systems(OnExit(AppState::Menu), menu::cleanup_menu);
+            .add_systems(OnExit(AppState::Menu), menu::cleanup_menu)
+            .add_systems(OnEnter(AppState::ModeSelect), menu::setup_mode_select)
+            .add_systems(Update, menu::mode_select)
+            .add_systems(OnExit(AppState::ModeSelect), menu::cleanup_menu);
     }
     // DEMO
     #[cfg(feature = "demo")]
@@ -97,6 +102,50 @@ fn main() {
     app.run();
 }
 
+/// `--pieces <file>` plays with a piece set from a RON or JSON file, e.g.
+/// `assets/pieces/pentominoes.ron`. `--survival` pushes up garbage rows from the bottom.
+/// `--puzzle <file>` offers a RON puzzle in the mode selection, e.g.
+/// `assets/puzzles/t_spin_double.ron`. `--modifier <name>` stacks a modifier on every mode and
+/// can be repeated, e.g. `--modifier mirror --modifier big`
+fn rules_from_args() -> RuleOptions {
+    #[allow(unused_mut)]
+    let mut rules = RuleOptions::default();
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let args: Vec<String> = std::env::args().collect();
+        let path = args
+            .iter()
+            .position(|arg| arg == "--pieces")
+            .and_then(|i| args.get(i + 1));
+        if let Some(path) = path {
+            match PieceSet::load(path) {
+                Ok(pieces) => rules.pieces = pieces,
+                Err(e) => eprintln!("{e}"),
+            }
+        }
+        let path = args
+            .iter()
+            .position(|arg| arg == "--puzzle")
+            .and_then(|i| args.get(i + 1));
+        if let Some(path) = path {
+            match Puzzle::load(path) {
+                Ok(puzzle) => rules.puzzle = Some(puzzle),
+                Err(e) => eprintln!("{e}"),
+            }
+        }
+        if args.iter().any(|arg| arg == "--survival") {
+            rules.survival = Some(SurvivalOptions::default());
+        }
+        for pair in args.windows(2).filter(|pair| pair[0] == "--modifier") {
+            match GameModifier::from_name(&pair[1]) {
+                Some(modifier) => rules.modifiers.push(modifier),
+                None => eprintln!("unknown modifier {}", pair[1]),
+            }
+        }
+    }
+    rules
+}
+
 #[cfg(feature = "ui")]
 fn configure_visuals(mut egui_ctx: Query<&mut EguiContext>) {
     egui_ctx.single_mut().get_mut().set_visuals(Visuals {
@@ -1,4 +1,5 @@
 use bevy::prelude::*;
+use tetris_plugin::{GameMode, RuleOptions};
 
 use crate::AppState;
 
@@ -14,6 +15,10 @@ pub use demo::setup_game;
 #[cfg_attr(feature = "debug", reflect(Component))]
 pub struct MenuComponent;
 
+/// Starts a game in the mode of the button
+#[derive(Component, Debug, Clone, Copy)]
+pub struct ModeButton(pub GameMode);
+
 const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
 const HOVERED_BUTTON: Color = Color::srgb(0.25, 0.25, 0.25);
 const PRESSED_BUTTON: Color = Color::srgb(0.35, 0.75, 0.35);
@@ -65,14 +70,101 @@ pub fn menu(
             &mut BorderColor,
             &Children,
         ),
-        (Changed<Interaction>, With<Button>),
+        (Changed<Interaction>, With<Button>, Without<ModeButton>),
     >,
 ) {
     for (interaction, mut background, mut _border, _children) in interaction_query.iter_mut() {
         match *interaction {
             Interaction::Pressed => {
                 *background = PRESSED_BUTTON.into();
-                println!("SET INGAME STATE");
+                state.set(AppState::ModeSelect);
+            }
+            Interaction::Hovered => {
+                *background = HOVERED_BUTTON.into();
+            }
+            Interaction::None => {
+                *background = NORMAL_BUTTON.into();
+            }
+        }
+    }
+}
+
+pub fn setup_mode_select(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    rules: Res<RuleOptions>,
+) {
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    let puzzle = rules.puzzle.as_ref().map(|_| GameMode::Puzzle);
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(10.0),
+                ..default()
+            },
+            MenuComponent,
+        ))
+        .with_children(|parent| {
+            for mode in GameMode::ALL.into_iter().chain(puzzle) {
+                parent
+                    .spawn((
+                        Button,
+                        Node {
+                            width: Val::Px(300.0),
+                            height: Val::Px(60.0),
+                            flex_direction: FlexDirection::Column,
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        BorderColor(Color::BLACK),
+                        BorderRadius::MAX,
+                        BackgroundColor(NORMAL_BUTTON),
+                        ModeButton(mode),
+                    ))
+                    .with_children(|button| {
+                        button.spawn((
+                            Text::new(mode.to_string()),
+                            TextFont {
+                                font: font.clone(),
+                                font_size: 30.0,
+                                ..default()
+                            },
+                            TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                        ));
+                        button.spawn((
+                            Text::new(mode.description()),
+                            TextFont {
+                                font: font.clone(),
+                                font_size: 15.0,
+                                ..default()
+                            },
+                            TextColor(Color::srgb(0.7, 0.7, 0.7)),
+                        ));
+                    });
+            }
+        });
+}
+
+/// Sets the game mode of the rules and starts the game
+pub fn mode_select(
+    mut state: ResMut<NextState<AppState>>,
+    mut rules: ResMut<RuleOptions>,
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, &ModeButton),
+        Changed<Interaction>,
+    >,
+) {
+    for (interaction, mut background, button) in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Pressed => {
+                *background = PRESSED_BUTTON.into();
+                rules.mode = button.0;
                 state.set(AppState::InGame);
             }
             Interaction::Hovered => {
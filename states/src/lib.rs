@@ -69,6 +69,8 @@ impl Display for GameLogicState {
 #[derive(Debug, Clone, Eq, PartialEq, Hash, States)]
 pub enum AppState {
     Menu,
+    /// Choosing the game mode before a game starts
+    ModeSelect,
     // PluginInit,
     InGame,
 }
@@ -95,6 +97,7 @@ impl Display for AppState {
             match self {
                 AppState::InGame => "Game is running",
                 AppState::Menu => "Main Menu",
+                AppState::ModeSelect => "Mode Selection",
             }
         )
     }
@@ -22,9 +22,40 @@ pub struct CurrentTetromino;
 #[derive(Component)]
 pub struct PopupRef;
 
+// Slot list of the pause menu, rebuilt when the save slots change
+#[derive(Component)]
+pub struct SaveSlotMenu;
+
+// Sends its command when the button is pressed
+#[derive(Component, Debug, Clone)]
+pub struct SlotButton(pub crate::SlotCommand);
+
 #[derive(Component)]
 pub struct PreviewRef;
 
+// Text about the last clear, removed when the timer finishes
+#[derive(Component)]
+pub struct CalloutRef(pub Timer);
+
+#[derive(Component)]
+pub struct HoldRef;
+
+// Time since startup when the block was locked, for modifiers changing the stack over time
+#[derive(Component, Debug, Clone, Copy)]
+pub struct LockedAt(pub std::time::Duration);
+
+// Colour of a garbage or wall block, blocks of pieces take it from their Tetromino
+#[derive(Component, Debug, Clone, Copy)]
+pub struct BaseColor(pub Color);
+
+// Block of the board layout, which modifiers leave as it is
+#[derive(Component)]
+pub struct Wall;
+
+// Translucent block showing where the current tetromino lands
+#[derive(Component)]
+pub struct GhostBlock;
+
 #[derive(Component)]
 pub struct TileMapRoot;
 
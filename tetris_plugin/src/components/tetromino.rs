@@ -1,6 +1,6 @@
 use bevy::prelude::*;
 
-use crate::{Shape, ShapePosition, ShapeType, TileBlueprint};
+use crate::{PieceSet, ShapePosition, ShapeType, TileBlueprint};
 
 // A block can be part of a tetromino. Stores the block's index within that
 // tetromino for the purpose of rotation.
@@ -13,15 +13,15 @@ pub struct Tetromino {
 }
 
 impl Tetromino {
-    #[deprecated]
-    pub fn blocks_from_type(shape_type: ShapeType) -> Vec<Tetromino> {
-        let blueprint = Shape::blueprint(shape_type);
-        blueprint
+    pub fn blocks_from_type(pieces: &PieceSet, shape_type: ShapeType) -> Vec<Tetromino> {
+        let color = pieces.color(shape_type);
+        pieces
+            .shape(shape_type)
             .positions
             .into_iter()
             .filter(|(_, b)| b == &TileBlueprint::CurrentTetromino)
             .map(|(pos, _)| Tetromino {
-                color: shape_type.get_color(),
+                color,
                 index: pos,
                 tetromino_type: shape_type,
             })
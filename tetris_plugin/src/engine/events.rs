@@ -0,0 +1,126 @@
+use bevy::prelude::Event;
+
+use crate::{
+    ClearResult, CollisionDetection, Coordinates, DropEvent, GameOverReason, HoldEvent, MoveEvent,
+    RotateEvent, ShapeType, SlideEvent,
+};
+
+/// Player input understood by the [`GameEngine`](crate::GameEngine).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum InputAction {
+    MoveLeft,
+    MoveRight,
+    MoveDown,
+    SlideLeft,
+    SlideRight,
+    RotateClockWise,
+    RotateCounterClockWise,
+    Hold,
+    HardDrop,
+    /// Soft drop is held until [`InputAction::SoftDropStop`]
+    SoftDropStart,
+    SoftDropStop,
+}
+
+impl From<MoveEvent> for InputAction {
+    fn from(event: MoveEvent) -> Self {
+        match event {
+            MoveEvent::Left => Self::MoveLeft,
+            MoveEvent::Right => Self::MoveRight,
+            MoveEvent::Down => Self::MoveDown,
+        }
+    }
+}
+
+impl From<SlideEvent> for InputAction {
+    fn from(event: SlideEvent) -> Self {
+        match event {
+            SlideEvent::Left => Self::SlideLeft,
+            SlideEvent::Right => Self::SlideRight,
+        }
+    }
+}
+
+impl From<RotateEvent> for InputAction {
+    fn from(event: RotateEvent) -> Self {
+        match event {
+            RotateEvent::ClockWise => Self::RotateClockWise,
+            RotateEvent::CounterClockWise => Self::RotateCounterClockWise,
+        }
+    }
+}
+
+impl From<HoldEvent> for InputAction {
+    fn from(_: HoldEvent) -> Self {
+        Self::Hold
+    }
+}
+
+impl From<DropEvent> for InputAction {
+    fn from(event: DropEvent) -> Self {
+        match event {
+            DropEvent::Hard => Self::HardDrop,
+            DropEvent::SoftStart => Self::SoftDropStart,
+            DropEvent::SoftStop => Self::SoftDropStop,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DropKind {
+    Soft,
+    Hard,
+}
+
+/// Everything that happened during a single engine step.
+///
+/// Cells are absolute board coordinates and always listed in the same order for one piece,
+/// so a renderer can keep one sprite per block.
+#[derive(Debug, Clone, PartialEq, Eq, Event)]
+pub enum GameEvent {
+    Spawned {
+        shape_type: ShapeType,
+        cells: Vec<Coordinates>,
+    },
+    Moved {
+        cells: Vec<Coordinates>,
+    },
+    Rotated {
+        cells: Vec<Coordinates>,
+    },
+    /// An input was rejected, the board is unchanged
+    Collision(CollisionDetection),
+    /// The active piece went into the hold slot and was removed from the board
+    Held {
+        shape_type: ShapeType,
+        cells: Vec<Coordinates>,
+    },
+    /// The player dropped the active piece by `rows` cells, scored separately per kind
+    Dropped {
+        kind: DropKind,
+        rows: u16,
+    },
+    Locked {
+        cells: Vec<Coordinates>,
+    },
+    LinesCleared {
+        lines: Vec<u16>,
+        transitions: Vec<(Coordinates, Coordinates)>,
+    },
+    /// The clear left an empty board
+    PerfectClear {
+        lines: u32,
+    },
+    /// Every lock reports its lines, spin and bonuses, even without cleared lines
+    Cleared(ClearResult),
+    Score(u64),
+    /// The level and with it the gravity changed, by clearing lines or a speed command
+    LevelChanged(u32),
+    /// A garbage row pushed the blocks up by one row, the cells are the new garbage blocks.
+    /// Walls stay, the transitions are the moved blocks.
+    GarbageAdded {
+        cells: Vec<Coordinates>,
+        transitions: Vec<(Coordinates, Coordinates)>,
+    },
+    GameOver(GameOverReason),
+}
@@ -0,0 +1,1565 @@
+//! Headless game rules.
+//!
+//! The [`GameEngine`] owns the [`Map`] and the active piece and advances the game one input or
+//! one tick at a time. It does not need a bevy `App`, the ECS systems only forward input and
+//! render the returned [`GameEvent`]s.
+pub use events::*;
+mod events;
+pub use piece::*;
+mod piece;
+pub use save::*;
+mod save;
+pub use scoring::*;
+mod scoring;
+pub use slot::*;
+mod slot;
+pub use spin::*;
+mod spin;
+
+use std::time::Duration;
+
+use bevy::prelude::Resource;
+use rand::{Rng, SeedableRng};
+
+use crate::{
+    CollisionDetection, Coordinates, GameMode, GameOverReason, GameRng, HandlingSettings, Level,
+    LockReset, Map, MoveEvent, PieceSet, Puzzle, PuzzleError, RotateEvent, RuleOptions, Score,
+    Sequence, ShapeQueue, ShapeType, Tile, LINES_PER_LEVEL,
+};
+
+/// Engine ticks per second, the gravity and lock delay are measured in ticks
+pub const TICKS_PER_SECOND: f64 = 60.0;
+
+#[derive(Debug, Clone, Resource)]
+pub struct GameEngine {
+    map: Map,
+    active: Option<ActivePiece>,
+    rules: RuleOptions,
+    queue: ShapeQueue,
+    hold: Option<ShapeType>,
+    /// Hold can be used once until the next piece locks
+    hold_used: bool,
+    /// Fraction of a cell the active piece has fallen since the last full cell
+    gravity_progress: f32,
+    soft_drop: bool,
+    soft_drop_factor: f32,
+    level: u32,
+    lines: u32,
+    /// Ticks the active piece has been resting on the stack
+    lock_ticks: u32,
+    lock_resets: u32,
+    /// Lowest row the rotation box of the active piece has reached
+    lowest_row: i16,
+    /// Kick test of the last successful action if it was a rotation, for T-spin detection
+    last_kick: Option<usize>,
+    scoring: Scoring,
+    ticks: u32,
+    /// Ticks since the last garbage row of the survival rules
+    garbage_ticks: u32,
+    garbage_rows: u32,
+    /// Hole column of the last garbage row of a dig game
+    last_hole: Option<u16>,
+    /// Cleared lines which contained garbage
+    garbage_lines: u32,
+    /// Pieces a puzzle has left to deal, `None` for an endless queue
+    pieces_left: Option<u32>,
+    /// Picks the holes of the garbage rows
+    rng: GameRng,
+    game_over: bool,
+}
+
+impl GameEngine {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self::from_map(Map::new(width, height))
+    }
+
+    /// Continues a game from a map. A current tetromino on the map becomes the active piece.
+    pub fn from_map(map: Map) -> Self {
+        let rules = RuleOptions::default();
+        let active = Self::find_active(&map, &rules.pieces);
+        let lowest_row = active.as_ref().map_or(i16::MIN, |p| p.position.1);
+        Self {
+            map,
+            active,
+            queue: ShapeQueue::new(rules.randomizer.generator(&rules.pieces)),
+            rules,
+            hold: None,
+            hold_used: false,
+            gravity_progress: 0.0,
+            soft_drop: false,
+            soft_drop_factor: HandlingSettings::default().soft_drop_factor,
+            level: 1,
+            lines: 0,
+            lock_ticks: 0,
+            lock_resets: 0,
+            lowest_row,
+            last_kick: None,
+            scoring: Scoring::default(),
+            ticks: 0,
+            garbage_ticks: 0,
+            garbage_rows: 0,
+            last_hole: None,
+            garbage_lines: 0,
+            pieces_left: None,
+            rng: GameRng::from_entropy(),
+            game_over: false,
+        }
+    }
+
+    /// Applies the rules. The shape queue is replaced with one fed by the configured randomizer,
+    /// the current tetromino on the map is looked up in the piece set of the rules.
+    pub fn with_rules(mut self, rules: &RuleOptions) -> Self {
+        self.queue = ShapeQueue::new(rules.randomizer.generator(&rules.pieces));
+        self.pieces_left = None;
+        let sequence = rules
+            .puzzle
+            .as_ref()
+            .filter(|_| rules.mode == GameMode::Puzzle)
+            .and_then(|puzzle| puzzle.shapes(&rules.pieces).ok())
+            .filter(|shapes| !shapes.is_empty());
+        if let Some(shapes) = sequence {
+            self.pieces_left = Some(shapes.len() as u32);
+            self.queue = ShapeQueue::new(Box::new(Sequence::new(shapes)));
+        }
+        self.rules = rules.clone();
+        self.level = rules.start_level.max(1);
+        self.active = Self::find_active(&self.map, &rules.pieces);
+        self.lowest_row = self.active.as_ref().map_or(i16::MIN, |p| p.position.1);
+        self
+    }
+
+    /// Starts a puzzle on its board, with the hidden rows of the rules above it
+    pub fn from_puzzle(puzzle: &Puzzle, rules: &RuleOptions) -> Result<Self, PuzzleError> {
+        puzzle.validate(&rules.pieces)?;
+        let rules = RuleOptions {
+            mode: GameMode::Puzzle,
+            puzzle: Some(puzzle.clone()),
+            ..rules.clone()
+        };
+        Ok(Self::from_map(puzzle.map(rules.hidden_rows)?).with_rules(&rules))
+    }
+
+    fn find_active(map: &Map, pieces: &PieceSet) -> Option<ActivePiece> {
+        let current: Vec<(Coordinates, char)> = map
+            .inner
+            .iter()
+            .filter_map(|(c, t)| match t {
+                Tile::CurrentTetromino(ch) => Some((*c, *ch)),
+                _ => None,
+            })
+            .collect();
+        let (_, ch) = current.first()?;
+        let cells: Vec<Coordinates> = current.iter().map(|(c, _)| *c).collect();
+        ActivePiece::from_cells(pieces, pieces.shape_type(*ch)?, &cells)
+    }
+
+    /// Gravity multiplier while soft drop is held, a handling setting of the player
+    pub fn set_soft_drop_factor(&mut self, factor: f32) {
+        self.soft_drop_factor = factor;
+    }
+
+    pub fn rules(&self) -> &RuleOptions {
+        &self.rules
+    }
+
+    pub fn map(&self) -> &Map {
+        &self.map
+    }
+
+    pub fn active(&self) -> Option<&ActivePiece> {
+        self.active.as_ref()
+    }
+
+    pub fn next_shape(&self) -> ShapeType {
+        self.queue.peek()
+    }
+
+    /// The upcoming shapes shown to the player, as many as configured in the rules
+    pub fn preview(&self) -> Vec<ShapeType> {
+        let count = match self.pieces_left {
+            Some(left) => self.rules.preview.min(left as usize),
+            None => self.rules.preview,
+        };
+        self.queue.preview(count)
+    }
+
+    /// Pieces a puzzle has left to deal, `None` outside of puzzles
+    pub fn pieces_left(&self) -> Option<u32> {
+        self.pieces_left
+    }
+
+    /// Takes the next shape from the queue, `None` if the puzzle sequence ran out
+    fn next_from_queue(&mut self) -> Option<ShapeType> {
+        if let Some(left) = self.pieces_left.as_mut() {
+            *left = left.checked_sub(1)?;
+        }
+        Some(self.queue.pop())
+    }
+
+    /// Cells where the active piece would land with a hard drop
+    pub fn ghost(&self) -> Vec<Coordinates> {
+        let distance = self.map.drop_distance() as i16;
+        self.active
+            .as_ref()
+            .map(|piece| piece.shifted((0, distance)).cells())
+            .unwrap_or_default()
+    }
+
+    pub fn hold(&self) -> Option<ShapeType> {
+        self.hold
+    }
+
+    /// False if the active piece was already swapped with the hold slot
+    pub fn can_hold(&self) -> bool {
+        !self.hold_used
+    }
+
+    pub fn score(&self) -> &Score {
+        self.scoring.score()
+    }
+
+    pub fn level(&self) -> u32 {
+        self.level
+    }
+
+    /// Lines cleared in this game
+    pub fn lines(&self) -> u32 {
+        self.lines
+    }
+
+    /// Lines counting towards the line goal of the game mode, only garbage lines in dig games
+    pub fn goal_lines(&self) -> u32 {
+        match self.rules.mode {
+            GameMode::Dig { .. } => self.garbage_lines,
+            _ => self.lines,
+        }
+    }
+
+    /// Cells per frame the active piece falls, without soft drop
+    pub fn gravity(&self) -> f32 {
+        self.rules
+            .gravity
+            .unwrap_or_else(|| Level(self.level).gravity())
+    }
+
+    /// Changes the level, line clears keep counting up from there. Levels start at 1.
+    pub fn set_level(&mut self, level: u32) -> Vec<GameEvent> {
+        let level = level.max(1);
+        if level == self.level {
+            return vec![];
+        }
+        self.level = level;
+        vec![GameEvent::LevelChanged(level)]
+    }
+
+    pub fn ticks(&self) -> u32 {
+        self.ticks
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        self.game_over
+    }
+
+    /// Places the next shape at the top of the board.
+    pub fn spawn(&mut self) -> Vec<GameEvent> {
+        if self.game_over || self.active.is_some() {
+            return vec![];
+        }
+        let mut events = self.refill_garbage();
+        if self.game_over {
+            return events;
+        }
+        // a held piece can still be played when the puzzle sequence ran out
+        let Some(shape_type) = self.next_from_queue().or_else(|| self.hold.take()) else {
+            events.push(self.end(GameOverReason::OutOfPieces));
+            return events;
+        };
+        events.extend(self.spawn_shape(shape_type));
+        events
+    }
+
+    /// Tops up the garbage rows of a dig game until the rest of the goal is on the board
+    fn refill_garbage(&mut self) -> Vec<GameEvent> {
+        let GameMode::Dig { rows, messiness } = self.rules.mode else {
+            return vec![];
+        };
+        let target = rows
+            .saturating_sub(self.garbage_lines)
+            .min(crate::DIG_ROWS_ON_BOARD);
+        let mut events = vec![];
+        for _ in self.map.garbage_rows()..target {
+            let hole = self.next_hole(messiness);
+            events.extend(self.add_garbage(hole));
+        }
+        events
+    }
+
+    /// Keeps the hole column of the previous garbage row, with a chance of `messiness` percent
+    /// it moves to another column
+    fn next_hole(&mut self, messiness: u8) -> u16 {
+        let width = self.map.width as u16;
+        let hole = match self.last_hole {
+            Some(last) if width > 1 && self.rng.gen_range(0..100) < messiness => {
+                (last + self.rng.gen_range(1..width)) % width
+            }
+            Some(last) => last,
+            None => self.rng.gen_range(0..width),
+        };
+        self.last_hole = Some(hole);
+        hole
+    }
+
+    /// Top left corner of the rotation box of a new piece. The box is centred on the board,
+    /// rounded to the left, in the two hidden rows right above the visible board.
+    fn spawn_position(&self, shape_type: ShapeType) -> (i16, i16) {
+        let size = self.rules.pieces.get(shape_type).size() as i16;
+        let y = (self.rules.hidden_rows as i16 - 2).max(0);
+        ((self.map.width as i16 - size) / 2, y)
+    }
+
+    /// True if the row is part of the hidden buffer above the visible board
+    fn is_hidden(&self, y: u16) -> bool {
+        y < self.rules.hidden_rows
+    }
+
+    /// Time since the game started
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_secs_f64(self.ticks as f64 / TICKS_PER_SECOND)
+    }
+
+    /// Time until the time limit of the game mode runs out
+    pub fn time_left(&self) -> Option<Duration> {
+        let limit = self.rules.mode.time_limit()?;
+        Some(limit.saturating_sub(self.elapsed()))
+    }
+
+    fn end(&mut self, reason: GameOverReason) -> GameEvent {
+        self.game_over = true;
+        GameEvent::GameOver(reason)
+    }
+
+    /// Ends the game, or clears the stack in game modes without top out
+    fn top_out(&mut self, reason: GameOverReason) -> GameEvent {
+        if self.rules.mode.tops_out() {
+            return self.end(reason);
+        }
+        let mut lines = vec![];
+        for (c, tile) in self.map.inner.iter_mut() {
+            if tile.is_block() {
+                *tile = Tile::Empty;
+                lines.push(c.y);
+            }
+        }
+        lines.sort();
+        lines.dedup();
+        GameEvent::LinesCleared {
+            lines,
+            transitions: vec![],
+        }
+    }
+
+    fn spawn_shape(&mut self, shape_type: ShapeType) -> Vec<GameEvent> {
+        self.gravity_progress = 0.0;
+        self.lock_ticks = 0;
+        self.lock_resets = 0;
+        self.lowest_row = i16::MIN;
+        self.last_kick = None;
+        let position = self.spawn_position(shape_type);
+        let piece = ActivePiece::new(&self.rules.pieces, shape_type, position);
+        let cells = piece.cells();
+        let mut events = vec![];
+        if cells
+            .iter()
+            .any(|c| !matches!(self.map.get(c), Some(Tile::Empty)))
+        {
+            events.push(self.top_out(GameOverReason::BlockOut));
+            if self.game_over {
+                return events;
+            }
+        }
+        self.place(piece);
+        events.push(GameEvent::Spawned { shape_type, cells });
+        events
+    }
+
+    /// Advances the game by one frame. The active piece falls by the gravity of the rules,
+    /// multiplied while soft drop is held. A piece resting on the stack locks after the lock
+    /// delay, the next piece is spawned by the following tick. Survival rules push up a garbage
+    /// row whenever their interval has passed.
+    pub fn tick(&mut self) -> Vec<GameEvent> {
+        if self.game_over {
+            return vec![];
+        }
+        self.ticks += 1;
+        if self.time_left() == Some(Duration::ZERO) {
+            return vec![self.end(GameOverReason::TimeUp)];
+        }
+        let mut events = self.rising_garbage();
+        if !self.game_over {
+            events.extend(self.advance());
+        }
+        events
+    }
+
+    fn advance(&mut self) -> Vec<GameEvent> {
+        if self.active.is_none() {
+            return self.spawn();
+        }
+        if self.map.detect_collision().is_some() {
+            self.gravity_progress = 0.0;
+            self.lock_ticks += 1;
+            let out_of_resets = self.rules.lock_reset == LockReset::Move
+                && self.lock_resets >= self.rules.lock_reset_limit;
+            if self.lock_ticks >= self.lock_delay_ticks() || out_of_resets {
+                return self.lock();
+            }
+            return vec![];
+        }
+        let factor = if self.soft_drop {
+            self.soft_drop_factor
+        } else {
+            1.0
+        };
+        self.gravity_progress += self.gravity() * factor;
+
+        let mut events = vec![];
+        let mut rows = 0;
+        while self.gravity_progress >= 1.0 {
+            self.gravity_progress -= 1.0;
+            match self.try_move(&MoveEvent::Down) {
+                Ok(cells) => {
+                    rows += 1;
+                    events.push(GameEvent::Moved { cells });
+                }
+                Err(_) => break,
+            }
+        }
+        events.extend(self.drop_score(DropKind::Soft, rows));
+        events
+    }
+
+    /// Counts the ticks until the next garbage row of the survival rules
+    fn rising_garbage(&mut self) -> Vec<GameEvent> {
+        let Some(survival) = &self.rules.survival else {
+            return vec![];
+        };
+        let interval = survival.interval(self.garbage_rows).as_secs_f64() * TICKS_PER_SECOND;
+        self.garbage_ticks += 1;
+        if self.garbage_ticks < interval.round() as u32 {
+            return vec![];
+        }
+        self.garbage_ticks = 0;
+        self.garbage_rows += 1;
+        let hole = self.rng.gen_range(0..self.map.width as u16);
+        self.add_garbage(hole)
+    }
+
+    /// Pushes a garbage row with a hole in the given column up from the bottom. An active piece
+    /// overlapping the raised stack is moved up one row. The game is over if blocks are pushed
+    /// off the map or the piece does not fit anymore.
+    pub fn add_garbage(&mut self, hole: u16) -> Vec<GameEvent> {
+        if self.game_over {
+            return vec![];
+        }
+        let pushed_off = (0..self.map.width as u16)
+            .any(|x| matches!(self.map.get(&(x, 0).into()), Some(tile) if tile.is_block()));
+        let active = self.active.take();
+        if let Some(piece) = &active {
+            for c in piece.cells() {
+                self.map.insert(c, Tile::Empty);
+            }
+        }
+        let (cells, transitions) = self.map.push_garbage_row(hole);
+        let mut events = vec![GameEvent::GarbageAdded { cells, transitions }];
+        if pushed_off {
+            events.push(self.top_out(GameOverReason::TopOut));
+            if self.game_over {
+                return events;
+            }
+        }
+        if let Some(mut piece) = active {
+            let mut moved = false;
+            if self.collision(&piece.cells()).is_some() {
+                let raised = piece.shifted((0, -1));
+                if self.collision(&raised.cells()).is_none() {
+                    piece = raised;
+                    moved = true;
+                } else {
+                    events.push(self.top_out(GameOverReason::TopOut));
+                    if self.game_over {
+                        return events;
+                    }
+                }
+            }
+            let cells = piece.cells();
+            self.place(piece);
+            if moved {
+                events.push(GameEvent::Moved { cells });
+            }
+        }
+        events
+    }
+
+    /// Applies a player input to the active piece.
+    pub fn apply(&mut self, action: InputAction) -> Vec<GameEvent> {
+        match action {
+            InputAction::SoftDropStart => self.soft_drop = true,
+            InputAction::SoftDropStop => self.soft_drop = false,
+            _ => (),
+        }
+        if self.game_over || self.active.is_none() {
+            return vec![];
+        }
+        match action {
+            InputAction::Hold => return self.swap_hold(),
+            InputAction::HardDrop => return self.hard_drop(),
+            InputAction::SlideLeft => return self.slide(&MoveEvent::Left),
+            InputAction::SlideRight => return self.slide(&MoveEvent::Right),
+            InputAction::SoftDropStart | InputAction::SoftDropStop => return vec![],
+            _ => (),
+        }
+        let result = match action {
+            InputAction::MoveLeft => self.try_move(&MoveEvent::Left),
+            InputAction::MoveRight => self.try_move(&MoveEvent::Right),
+            InputAction::MoveDown => self.try_move(&MoveEvent::Down),
+            InputAction::RotateClockWise => self.try_rotate(&RotateEvent::ClockWise),
+            InputAction::RotateCounterClockWise => self.try_rotate(&RotateEvent::CounterClockWise),
+            InputAction::Hold
+            | InputAction::HardDrop
+            | InputAction::SlideLeft
+            | InputAction::SlideRight
+            | InputAction::SoftDropStart
+            | InputAction::SoftDropStop => unreachable!("handled above"),
+        };
+        if result.is_ok() {
+            self.move_reset();
+        }
+        match (action, result) {
+            (_, Err(collision)) => vec![GameEvent::Collision(collision)],
+            (InputAction::RotateClockWise | InputAction::RotateCounterClockWise, Ok(cells)) => {
+                vec![GameEvent::Rotated { cells }]
+            }
+            (_, Ok(cells)) => vec![GameEvent::Moved { cells }],
+        }
+    }
+
+    fn lock_delay_ticks(&self) -> u32 {
+        (self.rules.lock_delay.as_secs_f64() * TICKS_PER_SECOND).round() as u32
+    }
+
+    /// Restarts the lock delay after a successful move or rotation, if the rules allow it
+    fn move_reset(&mut self) {
+        if self.rules.lock_reset == LockReset::Move
+            && self.lock_ticks > 0
+            && self.lock_resets < self.rules.lock_reset_limit
+        {
+            self.lock_ticks = 0;
+            self.lock_resets += 1;
+        }
+    }
+
+    /// Moves the active piece sideways until it hits something. Nothing happens if it cannot
+    /// move at all, so holding a direction against the wall stays silent.
+    fn slide(&mut self, event: &MoveEvent) -> Vec<GameEvent> {
+        let mut moved = None;
+        while let Ok(cells) = self.try_move(event) {
+            moved = Some(cells);
+        }
+        match moved {
+            Some(cells) => {
+                self.move_reset();
+                vec![GameEvent::Moved { cells }]
+            }
+            None => vec![],
+        }
+    }
+
+    /// Moves the active piece down as far as possible and locks it immediately.
+    fn hard_drop(&mut self) -> Vec<GameEvent> {
+        let mut events = vec![];
+        let mut rows = 0;
+        while let Ok(cells) = self.try_move(&MoveEvent::Down) {
+            rows += 1;
+            events.push(GameEvent::Moved { cells });
+        }
+        events.extend(self.drop_score(DropKind::Hard, rows));
+        events.extend(self.lock());
+        events
+    }
+
+    /// Scores the cells the active piece was dropped by the player
+    fn drop_score(&mut self, kind: DropKind, rows: u16) -> Vec<GameEvent> {
+        if rows == 0 || (kind == DropKind::Soft && !self.soft_drop) {
+            return vec![];
+        }
+        let points = self.scoring.drop(kind, rows);
+        vec![GameEvent::Dropped { kind, rows }, GameEvent::Score(points)]
+    }
+
+    /// Puts the active piece into the hold slot. The held shape, or the next one from the queue
+    /// if the slot was empty, spawns at the top.
+    fn swap_hold(&mut self) -> Vec<GameEvent> {
+        if self.hold_used || (self.hold.is_none() && self.pieces_left == Some(0)) {
+            return vec![];
+        }
+        let piece = self.active.take().expect("active piece");
+        let cells = piece.cells();
+        for c in cells.iter() {
+            self.map.insert(*c, Tile::Empty);
+        }
+        self.hold_used = true;
+        let next = match self.hold.replace(piece.shape_type) {
+            Some(shape_type) => shape_type,
+            None => self.next_from_queue().expect("checked above"),
+        };
+        let mut events = vec![GameEvent::Held {
+            shape_type: piece.shape_type,
+            cells,
+        }];
+        events.extend(self.spawn_shape(next));
+        events
+    }
+
+    fn try_move(&mut self, event: &MoveEvent) -> Result<Vec<Coordinates>, CollisionDetection> {
+        if let Some(collision) = self.map.detect_move_collision(event) {
+            return Err(collision);
+        }
+        let delta = match event {
+            MoveEvent::Down => (0, 1),
+            MoveEvent::Left => (-1, 0),
+            MoveEvent::Right => (1, 0),
+        };
+        let piece = self.active.as_ref().expect("active piece").shifted(delta);
+        let cells = piece.cells();
+        self.place(piece);
+        self.last_kick = None;
+        Ok(cells)
+    }
+
+    /// Rotates the active piece with the SRS wall kicks. The kicks are tested in order and the
+    /// first free position wins. The map is only changed if one of them fits.
+    fn try_rotate(
+        &mut self,
+        direction: &RotateEvent,
+    ) -> Result<Vec<Coordinates>, CollisionDetection> {
+        let active = self.active.as_ref().expect("active piece");
+        let rotated = active.rotated(direction);
+        let mut first_collision = None;
+        let kicks = self
+            .rules
+            .pieces
+            .get(active.shape_type)
+            .kicks
+            .wall_kicks(active.rotation, direction);
+        for (index, kick) in kicks.into_iter().enumerate() {
+            let piece = rotated.shifted(kick);
+            let cells = piece.cells();
+            match self.collision(&cells) {
+                None => {
+                    self.place(piece);
+                    self.last_kick = Some(index);
+                    return Ok(cells);
+                }
+                Some(collision) => {
+                    first_collision.get_or_insert(collision);
+                }
+            }
+        }
+        Err(first_collision.unwrap_or(CollisionDetection::Block))
+    }
+
+    /// Checks if the cells can be taken by the active piece
+    fn collision(&self, cells: &[Coordinates]) -> Option<CollisionDetection> {
+        cells.iter().find_map(|c| match self.map.get(c) {
+            Some(Tile::Block(_) | Tile::Garbage | Tile::Wall) => Some(CollisionDetection::Block),
+            Some(_) => None,
+            None if c.y == self.map.height as u16 => Some(CollisionDetection::Bottom),
+            None => Some(CollisionDetection::OutOfBounds),
+        })
+    }
+
+    /// Moves the active piece on the map. The new position must be checked for collisions before.
+    fn place(&mut self, piece: ActivePiece) {
+        if let Some(old) = self.active.take() {
+            for c in old.cells() {
+                self.map.insert(c, Tile::Empty);
+            }
+        }
+        let tile = Tile::CurrentTetromino(self.rules.pieces.symbol(piece.shape_type));
+        for c in piece.cells() {
+            self.map.insert(c, tile);
+        }
+        if piece.position.1 > self.lowest_row {
+            self.lowest_row = piece.position.1;
+            if self.rules.lock_reset != LockReset::None {
+                self.lock_ticks = 0;
+                self.lock_resets = 0;
+            }
+        }
+        self.active = Some(piece);
+    }
+
+    fn lock(&mut self) -> Vec<GameEvent> {
+        let piece = self.active.take().expect("active piece");
+        self.hold_used = false;
+        let cells = piece.cells();
+        let symbol = self.rules.pieces.symbol(piece.shape_type);
+        for c in cells.iter() {
+            self.map.insert(*c, Tile::Block(symbol));
+        }
+        let mut events = vec![GameEvent::Locked {
+            cells: cells.clone(),
+        }];
+        let lock_out = cells.iter().all(|c| self.is_hidden(c.y));
+
+        let mut lines: Vec<u16> = cells
+            .iter()
+            .map(|c| c.y)
+            .filter(|y| self.map.is_line_full(*y))
+            .collect();
+        lines.sort();
+        lines.dedup();
+        let spin = if self.rules.pieces.get(piece.shape_type).t_spin {
+            detect_spin(&self.map, &piece, self.last_kick)
+        } else {
+            SpinKind::None
+        };
+        let mut cleared = 0;
+        // follow the garbage through every step, falling garbage can complete later lines
+        let mut garbage = self.map.garbage_cells();
+        for (lines, transitions) in self.map.clear_lines(lines, self.rules.line_clear_gravity) {
+            cleared += lines.len() as u32;
+            self.garbage_lines += lines
+                .iter()
+                .filter(|y| garbage.iter().any(|c| c.y == **y))
+                .count() as u32;
+            garbage = garbage
+                .into_iter()
+                .filter(|c| !lines.contains(&c.y))
+                .map(|c| {
+                    transitions
+                        .iter()
+                        .find(|(from, _)| *from == c)
+                        .map_or(c, |(_, to)| *to)
+                })
+                .collect();
+            events.push(GameEvent::LinesCleared { lines, transitions });
+        }
+        let mut result = self.scoring.clear(cleared, spin, self.level);
+        if cleared > 0 && self.map.get_block_coordinates().is_empty() {
+            self.scoring.perfect_clear(&mut result, self.level);
+            events.push(GameEvent::PerfectClear { lines: cleared });
+        }
+        events.push(GameEvent::Cleared(result));
+        if result.points > 0 {
+            events.push(GameEvent::Score(result.points));
+        }
+
+        let level_ups = (self.lines + cleared) / LINES_PER_LEVEL - self.lines / LINES_PER_LEVEL;
+        self.lines += cleared;
+        if level_ups > 0 {
+            events.extend(self.set_level(self.level + level_ups));
+        }
+
+        let partial_lock_out = self.rules.partial_lock_out
+            && self
+                .map
+                .inner
+                .iter()
+                .any(|(c, tile)| tile.is_block() && self.is_hidden(c.y));
+        if lock_out {
+            events.push(self.top_out(GameOverReason::LockOut));
+        } else if partial_lock_out {
+            events.push(self.top_out(GameOverReason::PartialLockOut));
+        }
+        let goal = self.rules.mode.line_goal();
+        let solved = self.rules.mode == GameMode::Puzzle
+            && (self.rules.puzzle.as_ref()).is_some_and(|p| p.goal.is_met(self.lines, &result));
+        if !self.game_over && (solved || goal.is_some_and(|goal| self.goal_lines() >= goal)) {
+            events.push(self.end(GameOverReason::GoalReached));
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LineClearGravity, Randomizer, RotationState, ToMap, GRAVITY_TABLE};
+    use pretty_assertions::assert_eq;
+
+    /// Rules where the active piece falls one cell per tick and locks as soon as it lands.
+    /// The test maps have no hidden rows.
+    fn rules() -> RuleOptions {
+        RuleOptions {
+            gravity: Some(1.0),
+            lock_delay: Duration::ZERO,
+            hidden_rows: 0,
+            ..Default::default()
+        }
+    }
+
+    fn engine(input: Vec<&str>) -> GameEngine {
+        GameEngine::from_map(input.to_map()).with_rules(&rules())
+    }
+
+    fn test_rotate(input: Vec<&str>, res: Vec<&str>, direction: InputAction) {
+        let mut uut = engine(input);
+        let events = uut.apply(direction);
+        assert!(matches!(events[..], [GameEvent::Rotated { .. }]));
+        let expected: Map = res.to_map();
+        assert_eq!(uut.map(), &expected);
+    }
+
+    #[test]
+    fn test_rotate_s_shape_cw() {
+        let input = vec!["xxxxx", "xxSSx", "xSSxx", "xxxxx", "xxxxx"];
+        let res = vec!["xxxxx", "xxSxx", "xxSSx", "xxxSx", "xxxxx"];
+        test_rotate(input, res, InputAction::RotateClockWise);
+    }
+    #[test]
+    fn test_rotate_s_shape_ccw() {
+        let input = vec!["xxxxx", "xxSSx", "xSSxx", "xxxxx", "xxxxx"];
+        let res = vec!["xxxxx", "xSxxx", "xSSxx", "xxSxx", "xxxxx"];
+        test_rotate(input, res, InputAction::RotateCounterClockWise);
+    }
+    #[test]
+    fn test_rotate_z_shape_cw() {
+        let input = vec!["xxxxx", "xZZxx", "xxZZx", "xxxxx", "xxxxx"];
+        let res = vec!["xxxxx", "xxxZx", "xxZZx", "xxZxx", "xxxxx"];
+        test_rotate(input, res, InputAction::RotateClockWise);
+    }
+    #[test]
+    fn test_rotate_z_shape_ccw() {
+        let input = vec!["xxxxx", "xZZxx", "xxZZx", "xxxxx", "xxxxx"];
+        let res = vec!["xxxxx", "xxZxx", "xZZxx", "xZxxx", "xxxxx"];
+        test_rotate(input, res, InputAction::RotateCounterClockWise);
+    }
+    #[test]
+    fn test_rotate_t_shape_cw() {
+        let input = vec!["xxxxx", "xTxxx", "xTTxx", "xTxxx", "xxxxx"];
+        let res = vec!["xxxxx", "xxxxx", "TTTxx", "xTxxx", "xxxxx"];
+        test_rotate(input, res, InputAction::RotateClockWise);
+    }
+    #[test]
+    fn test_rotate_t_shape_ccw() {
+        let input = vec!["xxxxx", "xTxxx", "xTTxx", "xTxxx", "xxxxx"];
+        let res = vec!["xxxxx", "xTxxx", "TTTxx", "xxxxx", "xxxxx"];
+        test_rotate(input, res, InputAction::RotateCounterClockWise);
+    }
+
+    #[test]
+    fn test_rotate_o_shape_keeps_cells() {
+        let input = vec!["xxxxx", "xOOxx", "xOOxx", "xxxxx", "xxxxx"];
+        test_rotate(input.clone(), input, InputAction::RotateClockWise);
+    }
+    #[test]
+    fn test_rotate_i_shape_cw() {
+        let input = vec!["xxxxx", "xxxxx", "IIIIx", "xxxxx", "xxxxx"];
+        let res = vec!["xxxxx", "xxIxx", "xxIxx", "xxIxx", "xxIxx"];
+        test_rotate(input, res, InputAction::RotateClockWise);
+    }
+    #[test]
+    fn test_rotate_i_shape_kicks_off_left_wall() {
+        let input = vec![
+            "xxxxxx", "xxxxxx", "Ixxxxx", "Ixxxxx", "Ixxxxx", "Ixxxxx", "xxxxxx",
+        ];
+        let res = vec![
+            "xxxxxx", "xxxxxx", "xxxxxx", "xxxxxx", "IIIIxx", "xxxxxx", "xxxxxx",
+        ];
+        test_rotate(input, res, InputAction::RotateClockWise);
+    }
+    #[test]
+    fn test_rotate_t_shape_kicks_off_floor() {
+        let input = vec!["xxxxx", "xxxxx", "xxxxx", "xTxxx", "TTTxx"];
+        let res = vec!["xxxxx", "xxxxx", "Txxxx", "TTxxx", "Txxxx"];
+        test_rotate(input, res, InputAction::RotateClockWise);
+    }
+
+    #[test]
+    fn test_rejected_rotation_leaves_map_untouched() {
+        let input = vec!["oooooo", "ooTooo", "oTTToo", "oooooo"];
+        let mut uut = engine(input.clone());
+        let events = uut.apply(InputAction::RotateClockWise);
+        assert_eq!(
+            events,
+            vec![GameEvent::Collision(CollisionDetection::Block)]
+        );
+        assert_eq!(uut.map(), &input.to_map());
+        assert_eq!(uut.active().unwrap().rotation, RotationState::Spawn);
+    }
+
+    #[test]
+    fn test_move_blocked_leaves_map_untouched() {
+        let input = vec!["xxxxx", "TTTxx", "xTxxx", "xxxxx", "xxxxx"];
+        let mut uut = engine(input.clone());
+        let events = uut.apply(InputAction::MoveLeft);
+        assert_eq!(
+            events,
+            vec![GameEvent::Collision(CollisionDetection::OutOfBounds)]
+        );
+        assert_eq!(uut.map(), &input.to_map());
+    }
+
+    #[test]
+    fn test_tick_locks_and_clears_line() {
+        let input = vec![
+            "xxxxxxxxxx",
+            "xxxxxxxxxx",
+            "xxxxxxxxxx",
+            "xxxxxxxxOO",
+            "iiiiiiiiOO",
+        ];
+        let mut uut = engine(input);
+        let events = uut.tick();
+        assert_eq!(
+            events,
+            vec![
+                GameEvent::Locked {
+                    cells: vec![(8, 3).into(), (8, 4).into(), (9, 3).into(), (9, 4).into()]
+                },
+                GameEvent::LinesCleared {
+                    lines: vec![4],
+                    transitions: vec![
+                        ((8, 3).into(), (8, 4).into()),
+                        ((9, 3).into(), (9, 4).into())
+                    ]
+                },
+                GameEvent::Cleared(ClearResult {
+                    lines: 1,
+                    combo: Some(0),
+                    points: 100,
+                    ..Default::default()
+                }),
+                GameEvent::Score(100),
+            ]
+        );
+        let expected = vec![
+            "xxxxxxxxxx",
+            "xxxxxxxxxx",
+            "xxxxxxxxxx",
+            "xxxxxxxxxx",
+            "xxxxxxxxoo",
+        ];
+        assert_eq!(uut.map(), &expected.to_map());
+        assert!(uut.active().is_none());
+        assert!(matches!(uut.tick()[..], [GameEvent::Spawned { .. }]));
+    }
+
+    #[test]
+    fn test_spawn_takes_shapes_from_queue() {
+        let mut uut = GameEngine::new(10, 22).with_rules(&RuleOptions {
+            randomizer: Randomizer::SevenBag,
+            ..Default::default()
+        });
+        let mut spawned = vec![];
+        for _ in 0..7 {
+            let next = uut.next_shape();
+            match &uut.spawn()[..] {
+                [GameEvent::Spawned { shape_type, .. }] => spawned.push(*shape_type),
+                events => panic!("unexpected {events:?}"),
+            }
+            assert_eq!(spawned.last(), Some(&next));
+            uut.active = None;
+            uut.map = Map::new(10, 22);
+        }
+        for shape in PieceSet::tetrominoes().shape_types() {
+            assert!(spawned.contains(&shape));
+        }
+    }
+
+    #[test]
+    fn test_ghost_is_landing_position() {
+        let input = vec!["xTxxx", "TTTxx", "xxxxx", "xxxxx", "xxxxx", "xxxox"];
+        let mut uut = engine(input);
+        let expected: Vec<Coordinates> =
+            vec![(0, 5).into(), (1, 4).into(), (1, 5).into(), (2, 5).into()];
+        assert_eq!(uut.ghost(), expected);
+        uut.apply(InputAction::HardDrop);
+        assert!(uut.ghost().is_empty());
+    }
+
+    #[test]
+    fn test_level_up_every_ten_lines() {
+        let mut uut = engine(vec!["IIII", "xxxx", "oxxo"]);
+        uut.lines = 9;
+        let events = uut.apply(InputAction::HardDrop);
+        assert!(events.contains(&GameEvent::LevelChanged(2)));
+        assert_eq!(uut.lines(), 10);
+        assert_eq!(uut.level(), 2);
+    }
+
+    #[test]
+    fn test_gravity_follows_level() {
+        let mut uut = GameEngine::new(10, 22);
+        assert_eq!(uut.gravity(), GRAVITY_TABLE[0]);
+        assert_eq!(uut.set_level(0), vec![]);
+        assert_eq!(uut.set_level(30), vec![GameEvent::LevelChanged(30)]);
+        assert_eq!(uut.gravity(), 20.0);
+        // 20G drops a new piece to the floor within the first tick after spawning
+        uut.tick();
+        uut.tick();
+        assert_eq!(uut.map().drop_distance(), 0);
+    }
+
+    #[test]
+    fn test_t_spin_double() {
+        let input = vec!["xxxxx", "ooTxx", "oxTTo", "ooToo", "oooox"];
+        let mut uut = engine(input);
+        uut.apply(InputAction::RotateClockWise);
+        let events = uut.apply(InputAction::HardDrop);
+        assert!(events.contains(&GameEvent::Cleared(ClearResult {
+            lines: 2,
+            spin: SpinKind::TSpin,
+            combo: Some(0),
+            back_to_back: false,
+            perfect_clear: false,
+            points: 1200,
+        })));
+    }
+
+    #[test]
+    fn test_no_spin_without_rotation() {
+        let input = vec!["xxxxx", "xxxxx", "oTTTo", "ooTox", "oooox"];
+        let mut uut = engine(input);
+        let events = uut.tick();
+        assert!(events.contains(&GameEvent::Cleared(ClearResult {
+            lines: 1,
+            combo: Some(0),
+            points: 100,
+            ..Default::default()
+        })));
+    }
+
+    #[test]
+    fn test_perfect_clear() {
+        let input = vec!["xxxx", "xxxx", "ooxx", "ooOO", "ooOO"];
+        let mut uut = engine(input);
+        let events = uut.tick();
+        assert!(!events
+            .iter()
+            .any(|e| matches!(e, GameEvent::PerfectClear { .. })));
+        assert_eq!(uut.map().occupied(), 2);
+        assert_eq!(uut.score().perfect_clears, 0);
+
+        let input = vec!["xxxx", "xxxx", "xxxx", "ooOO", "ooOO"];
+        let mut uut = engine(input);
+        let events = uut.tick();
+        assert!(events.contains(&GameEvent::PerfectClear { lines: 2 }));
+        assert_eq!(uut.map().occupied(), 0);
+        assert_eq!(uut.score().perfect_clears, 1200);
+    }
+
+    #[test]
+    fn test_spawn_column_follows_width() {
+        for (width, shape_type, columns) in [
+            (10, ShapeType::T, 3..6),
+            (10, ShapeType::O, 4..6),
+            (10, ShapeType::I, 3..7),
+            (15, ShapeType::T, 6..9),
+            (6, ShapeType::I, 1..5),
+        ] {
+            let mut uut = GameEngine::new(width, 22);
+            uut.spawn_shape(shape_type);
+            let cells = uut.active().unwrap().cells();
+            assert!(
+                cells.iter().all(|c| columns.contains(&c.x)),
+                "{shape_type:?} on width {width}: {cells:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_slide_to_wall() {
+        let input = vec!["xxxTxx", "xxTTTx", "xxxxxx"];
+        let mut uut = engine(input);
+        let events = uut.apply(InputAction::SlideLeft);
+        assert_eq!(events.len(), 1);
+        let expected = vec!["xTxxxx", "TTTxxx", "xxxxxx"];
+        assert_eq!(uut.map(), &expected.to_map());
+        assert_eq!(uut.apply(InputAction::SlideLeft), vec![]);
+    }
+
+    #[test]
+    fn test_hard_drop_locks_at_lowest_position() {
+        let input = vec!["xTxxx", "TTTxx", "xxxxx", "xxxxx", "xxxxx", "xxxox"];
+        let mut uut = engine(input);
+        let events = uut.apply(InputAction::HardDrop);
+        let expected = vec!["xxxxx", "xxxxx", "xxxxx", "xxxxx", "xtxxx", "tttox"];
+        assert_eq!(uut.map(), &expected.to_map());
+        assert!(uut.active().is_none());
+        assert_eq!(
+            events[4..],
+            vec![
+                GameEvent::Dropped {
+                    kind: DropKind::Hard,
+                    rows: 4
+                },
+                GameEvent::Score(8),
+                GameEvent::Locked {
+                    cells: vec![(0, 5).into(), (1, 4).into(), (1, 5).into(), (2, 5).into()]
+                },
+                GameEvent::Cleared(ClearResult::default()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_soft_drop_multiplies_gravity() {
+        let input = vec!["xTxxx", "TTTxx", "xxxxx", "xxxxx", "xxxxx", "xxxxx"];
+        let mut uut = GameEngine::from_map(input.to_map()).with_rules(&RuleOptions {
+            gravity: Some(0.25),
+            hidden_rows: 0,
+            ..Default::default()
+        });
+        uut.set_soft_drop_factor(8.0);
+        assert_eq!(uut.tick(), vec![]);
+        assert_eq!(uut.apply(InputAction::SoftDropStart), vec![]);
+        let events = uut.tick();
+        assert_eq!(
+            events[2..],
+            vec![
+                GameEvent::Dropped {
+                    kind: DropKind::Soft,
+                    rows: 2
+                },
+                GameEvent::Score(2),
+            ]
+        );
+        uut.apply(InputAction::SoftDropStop);
+        assert_eq!(uut.tick(), vec![]);
+        assert_eq!(uut.score().drops, 2);
+    }
+
+    fn grounded(lock_reset: LockReset) -> GameEngine {
+        let input = vec!["xxxxxx", "xTxxxx", "TTTxxx"];
+        GameEngine::from_map(input.to_map()).with_rules(&RuleOptions {
+            lock_reset,
+            hidden_rows: 0,
+            ..Default::default()
+        })
+    }
+
+    fn is_locked(events: Vec<GameEvent>) -> bool {
+        events.iter().any(|e| matches!(e, GameEvent::Locked { .. }))
+    }
+
+    #[test]
+    fn test_lock_delay() {
+        let mut uut = grounded(LockReset::Move);
+        for _ in 0..29 {
+            assert!(!is_locked(uut.tick()));
+        }
+        assert!(is_locked(uut.tick()));
+    }
+
+    #[test]
+    fn test_move_resets_lock_delay() {
+        let mut uut = grounded(LockReset::Move);
+        for _ in 0..20 {
+            uut.tick();
+        }
+        assert!(matches!(
+            uut.apply(InputAction::MoveRight)[..],
+            [GameEvent::Moved { .. }]
+        ));
+        for _ in 0..29 {
+            assert!(!is_locked(uut.tick()));
+        }
+        assert!(is_locked(uut.tick()));
+    }
+
+    #[test]
+    fn test_move_reset_limit() {
+        let mut uut = grounded(LockReset::Move);
+        for i in 0..15 {
+            assert!(!is_locked(uut.tick()));
+            let action = if i % 2 == 0 {
+                InputAction::MoveRight
+            } else {
+                InputAction::MoveLeft
+            };
+            uut.apply(action);
+        }
+        assert!(is_locked(uut.tick()));
+    }
+
+    #[test]
+    fn test_step_and_no_reset_ignore_moves() {
+        for lock_reset in [LockReset::Step, LockReset::None] {
+            let mut uut = grounded(lock_reset);
+            for _ in 0..20 {
+                uut.tick();
+            }
+            uut.apply(InputAction::RotateClockWise);
+            for _ in 0..9 {
+                assert!(!is_locked(uut.tick()));
+            }
+            assert!(is_locked(uut.tick()), "{lock_reset:?}");
+        }
+    }
+
+    #[test]
+    fn test_step_reset_on_new_lowest_row() {
+        let input = vec!["xTxxxx", "TTTxxx", "xxxxxx", "xxxxxx", "oooxxx"];
+        let mut uut = GameEngine::from_map(input.to_map()).with_rules(&RuleOptions {
+            gravity: Some(1.0),
+            lock_reset: LockReset::Step,
+            hidden_rows: 0,
+            ..Default::default()
+        });
+        // rests on the ledge, then slides off and falls
+        for _ in 0..3 {
+            uut.tick();
+        }
+        for _ in 0..20 {
+            assert!(!is_locked(uut.tick()));
+        }
+        uut.apply(InputAction::MoveRight);
+        uut.apply(InputAction::MoveRight);
+        uut.apply(InputAction::MoveRight);
+        uut.tick();
+        for _ in 0..29 {
+            assert!(!is_locked(uut.tick()));
+        }
+        assert!(is_locked(uut.tick()));
+    }
+
+    #[test]
+    fn test_hold_once_per_lock() {
+        let mut uut = GameEngine::new(10, 22);
+        uut.spawn();
+        let first = uut.active().unwrap().shape_type;
+        let next = uut.next_shape();
+        let events = uut.apply(InputAction::Hold);
+        assert!(matches!(
+            &events[..],
+            [
+                GameEvent::Held { shape_type, .. },
+                GameEvent::Spawned { shape_type: spawned, .. }
+            ] if *shape_type == first && *spawned == next
+        ));
+        assert_eq!(uut.hold(), Some(first));
+        assert!(!uut.can_hold());
+        assert_eq!(uut.apply(InputAction::Hold), vec![]);
+
+        while !uut
+            .tick()
+            .iter()
+            .any(|e| matches!(e, GameEvent::Locked { .. }))
+        {}
+        assert!(uut.can_hold());
+        uut.tick();
+        let second = uut.active().unwrap().shape_type;
+        let events = uut.apply(InputAction::Hold);
+        assert!(matches!(
+            &events[..],
+            [
+                GameEvent::Held { shape_type, .. },
+                GameEvent::Spawned { shape_type: spawned, .. }
+            ] if *shape_type == second && *spawned == first
+        ));
+    }
+
+    #[test]
+    fn test_headless_game_ends() {
+        let mut uut = GameEngine::new(10, 22).with_rules(&rules());
+        let mut ticks = 0;
+        while !uut.is_game_over() {
+            uut.tick();
+            ticks += 1;
+            assert!(ticks < 10_000, "game must end without input");
+        }
+        assert_eq!(uut.tick(), vec![]);
+    }
+
+    #[test]
+    fn test_custom_piece_set() {
+        let pieces =
+            PieceSet::from_json(include_str!("../../../assets/pieces/trominoes.json")).unwrap();
+        let mut uut = GameEngine::new(6, 4).with_rules(&RuleOptions { pieces, ..rules() });
+        for _ in 0..2 {
+            let events = uut.spawn();
+            let [GameEvent::Spawned { cells, .. }] = &events[..] else {
+                panic!("{events:?}");
+            };
+            assert_eq!(cells.len(), 3);
+            uut.apply(InputAction::HardDrop);
+        }
+        let symbols: Vec<char> = uut
+            .map()
+            .inner
+            .values()
+            .filter_map(|tile| match tile {
+                Tile::Block(c) => Some(*c),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(symbols.len(), 6);
+        assert!(
+            symbols.iter().all(|c| *c == 'i' || *c == 'v'),
+            "{symbols:?}"
+        );
+    }
+
+    fn hidden_rules(hidden_rows: u16, partial_lock_out: bool) -> RuleOptions {
+        RuleOptions {
+            hidden_rows,
+            partial_lock_out,
+            ..rules()
+        }
+    }
+
+    fn game_over(events: &[GameEvent]) -> Option<GameOverReason> {
+        events.iter().find_map(|e| match e {
+            GameEvent::GameOver(reason) => Some(*reason),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn test_spawn_above_visible_board() {
+        let mut uut = GameEngine::new(10, 40).with_rules(&hidden_rules(20, true));
+        let events = uut.spawn();
+        let [GameEvent::Spawned { cells, .. }] = &events[..] else {
+            panic!("{events:?}");
+        };
+        assert!(cells.iter().all(|c| c.y == 18 || c.y == 19), "{cells:?}");
+    }
+
+    #[test]
+    fn test_block_out() {
+        // every shape overlaps the blocks in the spawn columns of the lower hidden row
+        let input = vec!["xxxxxxxxxx", "xxxooooxxx", "xxxoxxxxxx", "xxxoxxxxxx"];
+        for shape_type in PieceSet::tetrominoes().shape_types() {
+            let mut uut =
+                GameEngine::from_map(input.clone().to_map()).with_rules(&hidden_rules(2, true));
+            assert_eq!(
+                uut.spawn_shape(shape_type),
+                vec![GameEvent::GameOver(GameOverReason::BlockOut)],
+                "{shape_type:?}"
+            );
+            assert!(uut.is_game_over());
+        }
+    }
+
+    #[test]
+    fn test_lock_out() {
+        let input = vec!["xxxxxxxxxx", "xxxxIIIIxx", "xxxxooooxx", "xxxxooooxx"];
+        let mut uut = GameEngine::from_map(input.to_map()).with_rules(&hidden_rules(2, false));
+        let events = uut.apply(InputAction::HardDrop);
+        assert_eq!(game_over(&events), Some(GameOverReason::LockOut));
+        assert!(uut.is_game_over());
+    }
+
+    #[test]
+    fn test_partial_lock_out() {
+        let input = vec!["xxxxxxxxxx", "xxxxxxTxxx", "xxxxxTTTxx", "xxxxxoxxxx"];
+        let mut uut =
+            GameEngine::from_map(input.clone().to_map()).with_rules(&hidden_rules(2, true));
+        let events = uut.apply(InputAction::HardDrop);
+        assert_eq!(game_over(&events), Some(GameOverReason::PartialLockOut));
+
+        let mut uut = GameEngine::from_map(input.to_map()).with_rules(&hidden_rules(2, false));
+        assert_eq!(game_over(&uut.apply(InputAction::HardDrop)), None);
+        assert!(!uut.is_game_over());
+    }
+
+    #[test]
+    fn test_no_lock_out_when_clear_lowers_the_stack() {
+        let input = vec!["Ixxxxxxxxx", "Ixxxxxxxxx", "Iooooooooo", "Iooooooooo"];
+        let mut uut = GameEngine::from_map(input.to_map()).with_rules(&hidden_rules(2, true));
+        let events = uut.apply(InputAction::HardDrop);
+        assert_eq!(game_over(&events), None, "{events:?}");
+    }
+
+    fn mode_rules(mode: GameMode) -> RuleOptions {
+        RuleOptions {
+            mode,
+            ..hidden_rules(2, true)
+        }
+    }
+
+    #[test]
+    fn test_sprint_ends_at_line_goal() {
+        let input = vec!["xxxx", "Ixxx", "Ixxx", "Iooo", "Iooo"];
+        let mut uut =
+            GameEngine::from_map(input.to_map()).with_rules(&mode_rules(GameMode::Sprint));
+        uut.lines = 38;
+        let events = uut.apply(InputAction::HardDrop);
+        assert_eq!(game_over(&events), Some(GameOverReason::GoalReached));
+        assert_eq!(uut.lines(), 40);
+    }
+
+    #[test]
+    fn test_ultra_time_limit() {
+        let mut uut = GameEngine::new(10, 22).with_rules(&mode_rules(GameMode::Ultra));
+        uut.ticks = 120 * 60 - 2;
+        assert_eq!(game_over(&uut.tick()), None);
+        assert!(uut.time_left() > Some(Duration::ZERO));
+        assert_eq!(game_over(&uut.tick()), Some(GameOverReason::TimeUp));
+        assert!(uut.is_game_over());
+    }
+
+    #[test]
+    fn test_zen_clears_stack_instead_of_block_out() {
+        let input = vec!["xxxxxxxxxx", "xxxooooxxx", "xxxoxxxxxx", "xxxoxxxxxx"];
+        let mut uut = GameEngine::from_map(input.to_map()).with_rules(&mode_rules(GameMode::Zen));
+        let events = uut.spawn();
+        assert!(
+            matches!(
+                &events[..],
+                [GameEvent::LinesCleared { .. }, GameEvent::Spawned { .. }]
+            ),
+            "{events:?}"
+        );
+        assert!(!uut.is_game_over());
+        assert!(!uut.map().inner.values().any(|tile| tile.is_block()));
+    }
+
+    #[test]
+    fn test_dig_fills_and_refills_garbage() {
+        let dig = GameMode::Dig {
+            rows: 12,
+            messiness: 0,
+        };
+        let mut uut = GameEngine::new(10, 22).with_rules(&mode_rules(dig));
+        uut.spawn();
+        assert_eq!(uut.map().garbage_rows(), crate::DIG_ROWS_ON_BOARD);
+        let hole = uut.last_hole.unwrap();
+        assert!((12..22).all(|y| uut.map().get(&(hole, y).into()) == Some(&Tile::Empty)));
+
+        for y in 18..22 {
+            uut.map.insert((hole, y).into(), Tile::Garbage);
+        }
+        uut.map
+            .clear_lines(vec![18, 19, 20, 21], LineClearGravity::Naive);
+        uut.garbage_lines = 4;
+        uut.refill_garbage();
+        assert_eq!(uut.map().garbage_rows(), 8);
+    }
+
+    #[test]
+    fn test_dig_counts_only_garbage_lines() {
+        let dig = GameMode::Dig {
+            rows: 2,
+            messiness: 100,
+        };
+        let input = vec!["xxxx", "Ixxx", "Ixxx", "Iooo", "I###"];
+        let mut uut = GameEngine::from_map(input.to_map()).with_rules(&mode_rules(dig));
+        let events = uut.apply(InputAction::HardDrop);
+        assert_eq!(game_over(&events), None);
+        assert_eq!((uut.lines(), uut.goal_lines()), (2, 1));
+
+        let input = vec!["xxxx", "Ixxx", "Ixxx", "I###", "I###"];
+        let mut uut = GameEngine::from_map(input.to_map()).with_rules(&mode_rules(dig));
+        let events = uut.apply(InputAction::HardDrop);
+        assert_eq!(game_over(&events), Some(GameOverReason::GoalReached));
+    }
+
+    #[test]
+    fn test_dig_counts_garbage_lines_of_cascades() {
+        let dig = GameMode::Dig {
+            rows: 10,
+            messiness: 0,
+        };
+        let rules = RuleOptions {
+            line_clear_gravity: LineClearGravity::Cascade,
+            ..mode_rules(dig)
+        };
+        // the loose garbage falls into one row, which the cascade clears as well
+        let input = vec!["xxxx", "Ixxx", "Ix#x", "I#x#", "Ioo#"];
+        let mut uut = GameEngine::from_map(input.to_map()).with_rules(&rules);
+        let events = uut.apply(InputAction::HardDrop);
+        assert_eq!(game_over(&events), None);
+        assert_eq!(uut.map().garbage_rows(), 0);
+        assert_eq!((uut.lines(), uut.goal_lines()), (2, 2));
+    }
+
+    fn t_spin_double_puzzle() -> GameEngine {
+        let puzzle =
+            Puzzle::from_ron(include_str!("../../../assets/puzzles/t_spin_double.ron")).unwrap();
+        let mut uut = GameEngine::from_puzzle(&puzzle, &hidden_rules(2, true)).unwrap();
+        uut.spawn();
+        assert_eq!(uut.pieces_left(), Some(0));
+        uut
+    }
+
+    #[test]
+    fn test_puzzle_solved() {
+        let mut uut = t_spin_double_puzzle();
+        uut.apply(InputAction::MoveLeft);
+        uut.apply(InputAction::MoveLeft);
+        uut.apply(InputAction::RotateCounterClockWise);
+        while uut.map.detect_collision().is_none() {
+            uut.tick();
+        }
+        uut.apply(InputAction::RotateCounterClockWise);
+        let events = uut.apply(InputAction::HardDrop);
+        assert_eq!(game_over(&events), Some(GameOverReason::GoalReached));
+    }
+
+    #[test]
+    fn test_puzzle_out_of_pieces() {
+        let mut uut = t_spin_double_puzzle();
+        assert!(uut.preview().is_empty());
+        assert_eq!(uut.apply(InputAction::Hold), vec![]);
+        let events = uut.apply(InputAction::HardDrop);
+        assert_eq!(game_over(&events), None);
+        assert_eq!(
+            uut.spawn(),
+            vec![GameEvent::GameOver(GameOverReason::OutOfPieces)]
+        );
+    }
+
+    #[test]
+    fn test_garbage_pushes_stack_and_piece_up() {
+        let mut uut = engine(vec!["xxxx", "xxxx", "xTxx", "TTTo"]);
+        let events = uut.add_garbage(1);
+        assert_eq!(uut.map(), &vec!["xxxx", "xTxx", "TTTo", "#x##"].to_map());
+        assert!(matches!(
+            &events[..],
+            [GameEvent::GarbageAdded { .. }, GameEvent::Moved { .. }]
+        ));
+        assert!(!uut.is_game_over());
+    }
+
+    #[test]
+    fn test_garbage_top_out() {
+        let mut uut = engine(vec!["xxox", "xxxx", "xxxx"]);
+        let events = uut.add_garbage(0);
+        assert_eq!(game_over(&events), Some(GameOverReason::TopOut));
+
+        let mut uut = engine(vec!["xTxx", "TTTx", "oxxx"]);
+        let events = uut.add_garbage(0);
+        assert_eq!(game_over(&events), Some(GameOverReason::TopOut));
+    }
+
+    #[test]
+    fn test_survival_adds_garbage_after_interval() {
+        let rules = RuleOptions {
+            survival: Some(crate::SurvivalOptions {
+                start_interval: Duration::from_millis(50),
+                min_interval: Duration::from_millis(50),
+                speed_up: 1.0,
+            }),
+            ..rules()
+        };
+        let mut uut = GameEngine::new(10, 10).with_rules(&rules);
+        let added = |events: Vec<GameEvent>| {
+            events
+                .iter()
+                .any(|e| matches!(e, GameEvent::GarbageAdded { .. }))
+        };
+        assert!(!added(uut.tick()));
+        assert!(!added(uut.tick()));
+        assert!(added(uut.tick()));
+        let garbage = uut
+            .map()
+            .inner
+            .values()
+            .filter(|tile| **tile == Tile::Garbage)
+            .count();
+        assert_eq!(garbage, 9);
+    }
+}
@@ -0,0 +1,79 @@
+use crate::{Coordinates, PieceSet, RotateEvent, RotationState, ShapePosition, ShapeType};
+
+/// The piece which is currently controlled by the player.
+///
+/// `position` is the top left corner of the rotation box of the shape. It can be outside of the
+/// board, e.g. when a vertical I piece touches the left wall.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActivePiece {
+    pub shape_type: ShapeType,
+    pub position: (i16, i16),
+    pub rotation: RotationState,
+    /// Blocks inside the rotation box of every rotation state, taken from the piece set
+    blocks: [Vec<ShapePosition>; 4],
+}
+
+impl ActivePiece {
+    pub fn new(pieces: &PieceSet, shape_type: ShapeType, position: (i16, i16)) -> Self {
+        let piece = pieces.get(shape_type);
+        Self {
+            shape_type,
+            position,
+            rotation: RotationState::Spawn,
+            blocks: RotationState::ALL.map(|rotation| piece.blocks(rotation)),
+        }
+    }
+
+    /// Rebuilds a piece from the absolute coordinates of its blocks, e.g. from a loaded map.
+    /// The first rotation state which matches the blocks is used.
+    pub fn from_cells(
+        pieces: &PieceSet,
+        shape_type: ShapeType,
+        cells: &[Coordinates],
+    ) -> Option<Self> {
+        let mut cells = cells.to_vec();
+        cells.sort();
+        let spawned = Self::new(pieces, shape_type, (0, 0));
+        RotationState::ALL.into_iter().find_map(|rotation| {
+            let blocks = &spawned.blocks[rotation.index()];
+            let min = |f: fn(&ShapePosition) -> i16| blocks.iter().map(f).min().unwrap_or(0);
+            let position = (
+                cells.iter().map(|c| c.x).min()? as i16 - min(|p| p.x),
+                cells.iter().map(|c| c.y).min()? as i16 - min(|p| p.y),
+            );
+            let piece = Self {
+                position,
+                rotation,
+                ..spawned.clone()
+            };
+            let mut piece_cells = piece.cells();
+            piece_cells.sort();
+            (piece_cells == cells).then_some(piece)
+        })
+    }
+
+    /// Absolute board coordinates of all blocks.
+    /// Blocks left or above of the board are mapped to `u16::MAX`, which is never inside a map.
+    pub fn cells(&self) -> Vec<Coordinates> {
+        self.blocks[self.rotation.index()]
+            .iter()
+            .map(|pos| Coordinates {
+                x: u16::try_from(self.position.0 + pos.x).unwrap_or(u16::MAX),
+                y: u16::try_from(self.position.1 + pos.y).unwrap_or(u16::MAX),
+            })
+            .collect()
+    }
+
+    pub fn shifted(&self, (dx, dy): (i16, i16)) -> Self {
+        let mut piece = self.clone();
+        piece.position = (self.position.0 + dx, self.position.1 + dy);
+        piece
+    }
+
+    /// The piece rotated in place, without any wall kick applied.
+    pub fn rotated(&self, direction: &RotateEvent) -> Self {
+        let mut piece = self.clone();
+        piece.rotation = self.rotation.rotate(direction);
+        piece
+    }
+}
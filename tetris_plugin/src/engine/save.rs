@@ -0,0 +1,307 @@
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ActivePiece, GameEngine, GameRng, GeneratorState, Map, MapParseError, RotationState,
+    RuleOptions, Scoring, ShapeQueue, ShapeType, Tile,
+};
+
+/// Version of the [`SaveGame`] format. Saves which only contain a map are version 0.
+pub const SAVEGAME_VERSION: u32 = 1;
+
+/// The full state of a [`GameEngine`], a loaded game continues exactly where it was saved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveGame {
+    pub version: u32,
+    pub rules: RuleOptions,
+    /// All rows including the hidden ones, in the savegame format of [`Map`]
+    pub map: String,
+    pub active: Option<SavedPiece>,
+    /// Shapes dealt before the ones of the generator, the first one spawns next
+    pub queue: Vec<ShapeType>,
+    /// `None` for custom generators, they are replaced by a new generator of the rules
+    pub generator: Option<GeneratorState>,
+    pub hold: Option<ShapeType>,
+    pub hold_used: bool,
+    pub gravity_progress: f32,
+    pub level: u32,
+    pub lines: u32,
+    pub lock_ticks: u32,
+    pub lock_resets: u32,
+    pub lowest_row: i16,
+    pub last_kick: Option<usize>,
+    pub scoring: Scoring,
+    pub ticks: u32,
+    pub garbage_ticks: u32,
+    pub garbage_rows: u32,
+    pub last_hole: Option<u16>,
+    pub garbage_lines: u32,
+    pub pieces_left: Option<u32>,
+    #[serde(with = "crate::resources::saved_rng")]
+    pub rng: GameRng,
+    pub game_over: bool,
+}
+
+/// Shape, position and rotation of the active piece
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedPiece {
+    pub shape_type: ShapeType,
+    pub position: (i16, i16),
+    pub rotation: RotationState,
+}
+
+/// Only read first, so saves of a newer version are rejected before their fields are parsed
+#[derive(Deserialize)]
+struct Version {
+    version: u32,
+}
+
+impl SaveGame {
+    pub fn to_ron(&self) -> String {
+        ron::to_string(self).expect("savegames are always serializable")
+    }
+
+    /// Reads a save of any version. A save which only contains a map is migrated, it continues
+    /// with the given rules.
+    pub fn parse(text: &str, rules: &RuleOptions) -> Result<Self, SaveGameError> {
+        if !text.trim_start().starts_with('(') {
+            return Self::migrate(text, rules);
+        }
+        let Version { version } = ron::from_str(text).map_err(SaveGameError::Ron)?;
+        if version > SAVEGAME_VERSION {
+            return Err(SaveGameError::UnsupportedVersion(version));
+        }
+        ron::from_str(text).map_err(SaveGameError::Ron)
+    }
+
+    /// Version 0 stored only the visible map, the hidden rows of the rules are added above it.
+    /// Everything else starts over.
+    fn migrate(text: &str, rules: &RuleOptions) -> Result<Self, SaveGameError> {
+        let map = Map::parse(text)
+            .map_err(SaveGameError::Map)?
+            .with_hidden_rows(rules.hidden_rows);
+        Ok(GameEngine::from_map(map).with_rules(rules).save())
+    }
+}
+
+impl GameEngine {
+    pub fn save(&self) -> SaveGame {
+        SaveGame {
+            version: SAVEGAME_VERSION,
+            rules: self.rules.clone(),
+            map: self.map.as_savegame_string(),
+            active: self.active.as_ref().map(|piece| SavedPiece {
+                shape_type: piece.shape_type,
+                position: piece.position,
+                rotation: piece.rotation,
+            }),
+            queue: self.queue.shapes(),
+            generator: self.queue.generator_state(),
+            hold: self.hold,
+            hold_used: self.hold_used,
+            gravity_progress: self.gravity_progress,
+            level: self.level,
+            lines: self.lines,
+            lock_ticks: self.lock_ticks,
+            lock_resets: self.lock_resets,
+            lowest_row: self.lowest_row,
+            last_kick: self.last_kick,
+            scoring: self.scoring.clone(),
+            ticks: self.ticks,
+            garbage_ticks: self.garbage_ticks,
+            garbage_rows: self.garbage_rows,
+            last_hole: self.last_hole,
+            garbage_lines: self.garbage_lines,
+            pieces_left: self.pieces_left,
+            rng: self.rng.clone(),
+            game_over: self.game_over,
+        }
+    }
+
+    /// Continues a saved game with the rules it was saved with
+    pub fn from_save(save: SaveGame) -> Result<Self, SaveGameError> {
+        let invalid = |reason: &str| Err(SaveGameError::Invalid(reason.to_string()));
+        let pieces = &save.rules.pieces;
+        let shapes = pieces.shape_types();
+        let map = Map::parse(&save.map).map_err(SaveGameError::Map)?;
+        if map.width == 0 || map.height <= save.rules.hidden_rows as usize {
+            return invalid("the map is smaller than the hidden rows");
+        }
+        let known = |shape: &ShapeType| shapes.contains(shape);
+        if !save.queue.iter().chain(&save.hold).all(known) {
+            return invalid("a shape is not part of the piece set");
+        }
+        let active = match save.active {
+            Some(saved) if known(&saved.shape_type) => {
+                let mut piece = ActivePiece::new(pieces, saved.shape_type, saved.position);
+                piece.rotation = saved.rotation;
+                Some(piece)
+            }
+            Some(_) => return invalid("the active piece is not part of the piece set"),
+            None => None,
+        };
+        let mut on_map: Vec<_> = map
+            .inner
+            .iter()
+            .filter(|(_, tile)| matches!(tile, Tile::CurrentTetromino(_)))
+            .map(|(c, _)| *c)
+            .collect();
+        let mut cells = active.as_ref().map_or(vec![], ActivePiece::cells);
+        on_map.sort();
+        cells.sort();
+        if on_map != cells {
+            return invalid("the active piece does not match the map");
+        }
+
+        let generator = match save.generator {
+            Some(state) => state.generator(),
+            None => save.rules.randomizer.generator(pieces),
+        };
+        let mut engine = Self::from_map(map).with_rules(&save.rules);
+        engine.active = active;
+        engine.queue = ShapeQueue::with_shapes(generator, save.queue);
+        engine.hold = save.hold;
+        engine.hold_used = save.hold_used;
+        engine.gravity_progress = save.gravity_progress;
+        engine.level = save.level;
+        engine.lines = save.lines;
+        engine.lock_ticks = save.lock_ticks;
+        engine.lock_resets = save.lock_resets;
+        engine.lowest_row = save.lowest_row;
+        engine.last_kick = save.last_kick;
+        engine.scoring = save.scoring;
+        engine.ticks = save.ticks;
+        engine.garbage_ticks = save.garbage_ticks;
+        engine.garbage_rows = save.garbage_rows;
+        engine.last_hole = save.last_hole;
+        engine.garbage_lines = save.garbage_lines;
+        engine.pieces_left = save.pieces_left;
+        engine.rng = save.rng;
+        engine.game_over = save.game_over;
+        Ok(engine)
+    }
+}
+
+#[derive(Debug)]
+pub enum SaveGameError {
+    Ron(ron::error::SpannedError),
+    Map(MapParseError),
+    /// Saved by a newer version of the game
+    UnsupportedVersion(u32),
+    Invalid(String),
+    /// Reading or writing an exported file failed
+    Io(std::io::Error),
+}
+
+impl Display for SaveGameError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Ron(e) => write!(f, "invalid RON savegame: {e}"),
+            Self::Map(e) => write!(f, "invalid savegame map: {e}"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "savegame version {version} is not supported")
+            }
+            Self::Invalid(reason) => write!(f, "invalid savegame: {reason}"),
+            Self::Io(e) => write!(f, "savegame file: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for SaveGameError {}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{GameEvent, InputAction, Randomizer, ToMap};
+
+    fn rules() -> RuleOptions {
+        RuleOptions {
+            gravity: Some(0.5),
+            lock_delay: Duration::ZERO,
+            hidden_rows: 2,
+            randomizer: Randomizer::NesReroll,
+            ..Default::default()
+        }
+    }
+
+    fn play(engine: &mut GameEngine, ticks: u32) -> Vec<GameEvent> {
+        let mut events = vec![];
+        for tick in 0..ticks {
+            if engine.active().is_none() {
+                events.extend(engine.spawn());
+            }
+            if tick % 7 == 0 {
+                events.extend(engine.apply(InputAction::RotateClockWise));
+            }
+            events.extend(engine.tick());
+        }
+        events
+    }
+
+    #[test]
+    fn test_loaded_game_continues_like_the_saved_one() {
+        let mut engine = GameEngine::new(10, 22).with_rules(&rules());
+        engine.spawn();
+        play(&mut engine, 150);
+        engine.apply(InputAction::Hold);
+
+        let save = SaveGame::parse(&engine.save().to_ron(), &RuleOptions::default()).unwrap();
+        assert_eq!(save.to_ron(), engine.save().to_ron());
+        let mut loaded = GameEngine::from_save(save).unwrap();
+        assert_eq!(loaded.active(), engine.active());
+        assert_eq!(loaded.preview(), engine.preview());
+        assert_eq!(play(&mut loaded, 400), play(&mut engine, 400));
+        assert_eq!(loaded.save().to_ron(), engine.save().to_ron());
+    }
+
+    #[test]
+    fn test_migrate_map_save() {
+        let map = vec!["xxxTxxxx", "xxTTTxxx", "xxxxxxxx", "ooxxxxxx"]
+            .to_map()
+            .as_savegame_string();
+        let save = SaveGame::parse(&map, &rules()).unwrap();
+        assert_eq!(save.version, SAVEGAME_VERSION);
+        let engine = GameEngine::from_save(save).unwrap();
+        // the hidden rows are added above the saved map
+        assert_eq!((engine.map().width, engine.map().height), (8, 6));
+        let piece = engine.active().unwrap();
+        assert_eq!(piece.rotation, RotationState::Spawn);
+        assert_eq!(piece.position, (2, 2));
+    }
+
+    #[test]
+    fn test_migrate_baseline_sized_save() {
+        let mut map = Map::new(10, 22);
+        map.insert((0, 21).into(), Tile::Block('o'));
+        let rules = RuleOptions::default();
+        let save = SaveGame::parse(&map.as_savegame_string(), &rules).unwrap();
+        let engine = GameEngine::from_save(save).unwrap();
+        let visible = engine.map().height - rules.hidden_rows as usize;
+        assert_eq!((engine.map().width, visible), (10, 22));
+        let bottom = (0, 21 + rules.hidden_rows).into();
+        assert_eq!(engine.map().get(&bottom), Some(&Tile::Block('o')));
+    }
+
+    #[test]
+    fn test_reject_newer_and_broken_saves() {
+        let mut save = GameEngine::new(10, 22).with_rules(&rules()).save();
+        save.version = SAVEGAME_VERSION + 1;
+        assert!(matches!(
+            SaveGame::parse(&save.to_ron(), &rules()),
+            Err(SaveGameError::UnsupportedVersion(_))
+        ));
+        save.version = SAVEGAME_VERSION;
+        save.active = Some(SavedPiece {
+            shape_type: ShapeType::T,
+            position: (3, 0),
+            rotation: RotationState::Spawn,
+        });
+        assert!(matches!(
+            GameEngine::from_save(save),
+            Err(SaveGameError::Invalid(_))
+        ));
+    }
+}
@@ -0,0 +1,252 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{DropKind, Score, SpinKind};
+
+/// Points per cell a piece falls while soft drop is held
+const SOFT_DROP_POINTS: u64 = 1;
+/// Points per cell a piece falls with a hard drop
+const HARD_DROP_POINTS: u64 = 2;
+/// Points by cleared lines, from none to a tetris, multiplied by the level
+pub const LINE_CLEAR_POINTS: [u64; 5] = [0, 100, 300, 500, 800];
+/// Points of a mini T-spin by cleared lines, multiplied by the level
+pub const MINI_T_SPIN_POINTS: [u64; 3] = [100, 200, 400];
+/// Points of a T-spin by cleared lines, multiplied by the level
+pub const T_SPIN_POINTS: [u64; 4] = [400, 800, 1200, 1600];
+/// Points per combo step, multiplied by the level
+pub const COMBO_POINTS: u64 = 50;
+/// Perfect clear bonus by cleared lines, multiplied by the level
+pub const PERFECT_CLEAR_POINTS: [u64; 5] = [0, 800, 1200, 1800, 2000];
+/// Perfect clear bonus of a back-to-back tetris, multiplied by the level
+pub const BACK_TO_BACK_PERFECT_CLEAR_POINTS: u64 = 3200;
+
+/// Outcome of a single lock
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct ClearResult {
+    pub lines: u32,
+    pub spin: SpinKind,
+    /// Number of clearing locks in a row minus one, `None` if no lines were cleared
+    pub combo: Option<u32>,
+    /// The back-to-back bonus was awarded
+    pub back_to_back: bool,
+    /// The board is empty after the clear
+    pub perfect_clear: bool,
+    pub points: u64,
+}
+
+impl ClearResult {
+    /// Text shown to the player, e.g. "Back-to-Back T-Spin Double". `None` for plain locks
+    /// and singles.
+    pub fn callout(&self) -> Option<String> {
+        let mut lines = vec![];
+        if self.perfect_clear {
+            lines.push("Perfect Clear!".to_string());
+        }
+        let mut parts = vec![];
+        if self.back_to_back {
+            parts.push("Back-to-Back");
+        }
+        match self.spin {
+            SpinKind::None => (),
+            SpinKind::Mini => parts.push("Mini T-Spin"),
+            SpinKind::TSpin => parts.push("T-Spin"),
+        }
+        match self.lines {
+            0 => (),
+            1 if self.spin == SpinKind::None => (),
+            1 => parts.push("Single"),
+            2 => parts.push("Double"),
+            3 => parts.push("Triple"),
+            _ => parts.push("Tetris"),
+        }
+        if !parts.is_empty() {
+            lines.push(parts.join(" "));
+        }
+        if let Some(combo @ 1..) = self.combo {
+            lines.push(format!("{} Combo", combo));
+        }
+        (!lines.is_empty()).then(|| lines.join("\n"))
+    }
+}
+
+/// Guideline scoring. Keeps the combo and back-to-back state between locks.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Scoring {
+    score: Score,
+    combo: Option<u32>,
+    /// The last clear was a difficult one
+    back_to_back: bool,
+}
+
+impl Scoring {
+    pub fn score(&self) -> &Score {
+        &self.score
+    }
+
+    /// Current combo, 0 for the first clearing lock
+    pub fn combo(&self) -> Option<u32> {
+        self.combo
+    }
+
+    /// Scores the cells a piece was dropped by the player
+    pub fn drop(&mut self, kind: DropKind, rows: u16) -> u64 {
+        let points = match kind {
+            DropKind::Soft => SOFT_DROP_POINTS,
+            DropKind::Hard => HARD_DROP_POINTS,
+        } * rows as u64;
+        self.score.drops += points;
+        points
+    }
+
+    /// Scores a lock which cleared `lines`. A lock without lines ends the combo. Tetrises and
+    /// spins which clear lines are difficult, any other clear ends the back-to-back chain.
+    pub fn clear(&mut self, lines: u32, spin: SpinKind, level: u32) -> ClearResult {
+        let level = level.max(1) as u64;
+        let table: &[u64] = match spin {
+            SpinKind::None => &LINE_CLEAR_POINTS,
+            SpinKind::Mini => &MINI_T_SPIN_POINTS,
+            SpinKind::TSpin => &T_SPIN_POINTS,
+        };
+        let points = table[(lines as usize).min(table.len() - 1)] * level;
+        self.score.line_clears += points;
+        if lines == 0 {
+            self.combo = None;
+            return ClearResult {
+                spin,
+                points,
+                ..Default::default()
+            };
+        }
+
+        let difficult = lines >= 4 || spin != SpinKind::None;
+        let back_to_back = difficult && self.back_to_back;
+        let back_to_back_points = if back_to_back { points / 2 } else { 0 };
+        self.back_to_back = difficult;
+
+        let combo = self.combo.map_or(0, |combo| combo + 1);
+        self.combo = Some(combo);
+        let combo_points = COMBO_POINTS * combo as u64 * level;
+
+        self.score.back_to_back += back_to_back_points;
+        self.score.combos += combo_points;
+        ClearResult {
+            lines,
+            spin,
+            combo: Some(combo),
+            back_to_back,
+            perfect_clear: false,
+            points: points + back_to_back_points + combo_points,
+        }
+    }
+
+    /// Adds the perfect clear bonus to a clear which left an empty board
+    pub fn perfect_clear(&mut self, result: &mut ClearResult, level: u32) {
+        let level = level.max(1) as u64;
+        let points = if result.back_to_back && result.lines >= 4 {
+            BACK_TO_BACK_PERFECT_CLEAR_POINTS
+        } else {
+            PERFECT_CLEAR_POINTS[(result.lines as usize).min(PERFECT_CLEAR_POINTS.len() - 1)]
+        } * level;
+        self.score.perfect_clears += points;
+        result.perfect_clear = true;
+        result.points += points;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clear(uut: &mut Scoring, lines: u32, level: u32) -> u64 {
+        uut.clear(lines, SpinKind::None, level).points
+    }
+
+    #[test]
+    fn test_line_clear_tiers() {
+        let mut uut = Scoring::default();
+        assert_eq!(clear(&mut uut, 1, 1), 100);
+        clear(&mut uut, 0, 1);
+        assert_eq!(clear(&mut uut, 2, 2), 600);
+        clear(&mut uut, 0, 1);
+        assert_eq!(clear(&mut uut, 3, 1), 500);
+        clear(&mut uut, 0, 1);
+        assert_eq!(clear(&mut uut, 4, 1), 800);
+        assert_eq!(uut.score().total(), 2000);
+    }
+
+    #[test]
+    fn test_combo() {
+        let mut uut = Scoring::default();
+        assert_eq!(clear(&mut uut, 1, 1), 100);
+        assert_eq!(clear(&mut uut, 1, 1), 150);
+        assert_eq!(clear(&mut uut, 1, 2), 400);
+        assert_eq!(uut.combo(), Some(2));
+        assert_eq!(clear(&mut uut, 0, 1), 0);
+        assert_eq!(uut.combo(), None);
+        assert_eq!(uut.score().combos, 250);
+    }
+
+    #[test]
+    fn test_back_to_back() {
+        let mut uut = Scoring::default();
+        clear(&mut uut, 4, 1);
+        clear(&mut uut, 0, 1);
+        // a lock without lines keeps the chain
+        assert_eq!(clear(&mut uut, 4, 1), 1200);
+        clear(&mut uut, 0, 1);
+        // a single breaks it
+        clear(&mut uut, 1, 1);
+        clear(&mut uut, 0, 1);
+        assert_eq!(clear(&mut uut, 4, 1), 800);
+        assert_eq!(uut.score().back_to_back, 400);
+    }
+
+    #[test]
+    fn test_t_spins() {
+        let mut uut = Scoring::default();
+        assert_eq!(uut.clear(0, SpinKind::TSpin, 1).points, 400);
+        assert_eq!(uut.clear(0, SpinKind::Mini, 1).points, 100);
+        assert_eq!(uut.clear(1, SpinKind::Mini, 1).points, 200);
+        clear(&mut uut, 0, 1);
+        // a mini clear is difficult as well
+        let result = uut.clear(2, SpinKind::TSpin, 1);
+        assert_eq!(result.points, 1800);
+        assert_eq!(
+            result.callout().as_deref(),
+            Some("Back-to-Back T-Spin Double")
+        );
+    }
+
+    #[test]
+    fn test_callout() {
+        let mut uut = Scoring::default();
+        assert_eq!(uut.clear(1, SpinKind::None, 1).callout(), None);
+        assert_eq!(
+            uut.clear(4, SpinKind::None, 1).callout().as_deref(),
+            Some("Tetris\n1 Combo")
+        );
+    }
+
+    #[test]
+    fn test_perfect_clear() {
+        let mut uut = Scoring::default();
+        let mut result = uut.clear(2, SpinKind::None, 2);
+        uut.perfect_clear(&mut result, 2);
+        assert_eq!(result.points, 600 + 2400);
+        assert_eq!(result.callout().as_deref(), Some("Perfect Clear!\nDouble"));
+        clear(&mut uut, 0, 1);
+        uut.clear(4, SpinKind::None, 1);
+        clear(&mut uut, 0, 1);
+        let mut result = uut.clear(4, SpinKind::None, 1);
+        uut.perfect_clear(&mut result, 1);
+        assert_eq!(result.points, 1200 + 3200);
+        assert_eq!(uut.score().perfect_clears, 5600);
+    }
+
+    #[test]
+    fn test_drop_points() {
+        let mut uut = Scoring::default();
+        assert_eq!(uut.drop(DropKind::Soft, 3), 3);
+        assert_eq!(uut.drop(DropKind::Hard, 3), 6);
+        assert_eq!(uut.score().drops, 9);
+    }
+}
@@ -0,0 +1,139 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Coordinates, GameEngine, GameMode, Map, RuleOptions, SaveGame, SaveGameError, Tile};
+
+/// Shown for empty cells in the thumbnail, so the board keeps its shape in proportional fonts
+pub const THUMBNAIL_EMPTY: char = '.';
+
+/// A named [`SaveGame`] with what the slot list shows about it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SaveSlot {
+    pub name: String,
+    /// Seconds since the unix epoch
+    pub timestamp: u64,
+    pub mode: GameMode,
+    pub score: u64,
+    pub level: u32,
+    /// The visible rows of the board, one line per row
+    pub thumbnail: String,
+    /// The [`SaveGame`] as RON, it is versioned on its own and migrated when it is loaded
+    pub save: String,
+}
+
+impl SaveSlot {
+    pub fn new(name: impl Into<String>, engine: &GameEngine, timestamp: u64) -> Self {
+        Self {
+            name: name.into(),
+            timestamp,
+            mode: engine.rules().mode,
+            score: engine.score().total(),
+            level: engine.level(),
+            thumbnail: thumbnail(engine.map(), engine.rules().hidden_rows),
+            save: engine.save().to_ron(),
+        }
+    }
+
+    /// Wraps a save of the single slot older versions had
+    pub fn migrate(
+        name: impl Into<String>,
+        text: &str,
+        rules: &RuleOptions,
+        timestamp: u64,
+    ) -> Result<Self, SaveGameError> {
+        let engine = SaveGame::parse(text, rules).and_then(GameEngine::from_save)?;
+        Ok(Self::new(name, &engine, timestamp))
+    }
+
+    /// The game saved in the slot, the rules are only used by saves which contain just a map
+    pub fn game(&self, rules: &RuleOptions) -> Result<GameEngine, SaveGameError> {
+        SaveGame::parse(&self.save, rules).and_then(GameEngine::from_save)
+    }
+
+    pub fn to_ron(&self) -> String {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .expect("save slots are always serializable")
+    }
+
+    pub fn parse(text: &str) -> Result<Self, SaveGameError> {
+        ron::from_str(text).map_err(SaveGameError::Ron)
+    }
+
+    /// Writes the slot to a file which [`SaveSlot::import`] reads on another machine
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn export(&self, path: impl AsRef<std::path::Path>) -> Result<(), SaveGameError> {
+        std::fs::write(path, self.to_ron()).map_err(SaveGameError::Io)
+    }
+
+    /// Reads an exported slot, the saved game is checked before the slot is returned
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn import(path: impl AsRef<std::path::Path>) -> Result<Self, SaveGameError> {
+        let text = std::fs::read_to_string(path).map_err(SaveGameError::Io)?;
+        let slot = Self::parse(&text)?;
+        slot.game(&RuleOptions::default())?;
+        Ok(slot)
+    }
+}
+
+/// Text preview of the visible rows, blocks keep their map symbols
+fn thumbnail(map: &Map, hidden_rows: u16) -> String {
+    (hidden_rows..map.height as u16)
+        .map(|y| {
+            (0..map.width as u16)
+                .map(|x| match map.get(&Coordinates { x, y }) {
+                    Some(Tile::Empty) | None => THUMBNAIL_EMPTY,
+                    Some(tile) => tile.symbol(),
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ToMap;
+
+    fn engine() -> GameEngine {
+        let map = vec!["xxxTxx", "xxTTTx", "xxxxxx", "=ooxx#"].to_map();
+        let rules = RuleOptions {
+            hidden_rows: 1,
+            mode: GameMode::Sprint,
+            ..Default::default()
+        };
+        GameEngine::from_map(map).with_rules(&rules)
+    }
+
+    #[test]
+    fn test_slot_metadata_and_thumbnail() {
+        let engine = engine();
+        let slot = SaveSlot::new("bug", &engine, 1_700_000_000);
+        assert_eq!(slot.mode, GameMode::Sprint);
+        assert_eq!((slot.score, slot.level), (0, 1));
+        assert_eq!(slot.thumbnail, "..TTT.\n......\n=oo..#");
+
+        let parsed = SaveSlot::parse(&slot.to_ron()).unwrap();
+        assert_eq!(parsed, slot);
+        let loaded = parsed.game(&RuleOptions::default()).unwrap();
+        assert_eq!(loaded.save().to_ron(), engine.save().to_ron());
+    }
+
+    #[test]
+    fn test_migrate_single_savegame() {
+        let text = engine().save().to_ron();
+        let slot = SaveSlot::migrate("Slot 1", &text, &RuleOptions::default(), 0).unwrap();
+        assert_eq!(slot.save, text);
+        assert!(SaveSlot::migrate("Slot 1", "xx?", &RuleOptions::default(), 0).is_err());
+    }
+
+    #[test]
+    fn test_export_and_import() {
+        let slot = SaveSlot::new("bug", &engine(), 1_700_000_000);
+        let path = std::env::temp_dir().join(format!("tetris-slot-{}.ron", std::process::id()));
+        slot.export(&path).unwrap();
+        let imported = SaveSlot::import(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(imported.unwrap(), slot);
+        assert!(matches!(SaveSlot::import(&path), Err(SaveGameError::Io(_))));
+    }
+}
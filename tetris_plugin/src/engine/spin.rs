@@ -0,0 +1,54 @@
+use crate::{ActivePiece, Coordinates, Map, RotationState, Tile};
+
+/// Spin of a locked piece, decided by the 3-corner rule
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum SpinKind {
+    #[default]
+    None,
+    Mini,
+    TSpin,
+}
+
+/// Kick test which always makes a full T-spin, e.g. a T-spin triple kick
+const UPGRADE_KICK: usize = 4;
+
+/// Checks the corners around the centre of a T piece which was rotated into its final position.
+/// Only called for pieces of the [`crate::PieceSet`] with T-spin detection.
+/// `last_kick` is the kick test used by the last successful action, `None` if that was no
+/// rotation.
+///
+/// Three occupied corners make a T-spin. It is a mini unless both corners on the side the T
+/// points to are occupied, or the last kick test was used. Walls and the floor count as occupied.
+pub fn detect_spin(map: &Map, piece: &ActivePiece, last_kick: Option<usize>) -> SpinKind {
+    let Some(kick) = last_kick else {
+        return SpinKind::None;
+    };
+    let (cx, cy) = (piece.position.0 + 1, piece.position.1 + 1);
+    let occupied = |(dx, dy): (i16, i16)| {
+        let (x, y) = (cx + dx, cy + dy);
+        if x < 0 || y < 0 {
+            return true;
+        }
+        let coordinates = Coordinates {
+            x: x as u16,
+            y: y as u16,
+        };
+        !matches!(map.get(&coordinates), Some(Tile::Empty))
+    };
+
+    let corners = [(-1, -1), (1, -1), (-1, 1), (1, 1)];
+    if corners.into_iter().filter(|c| occupied(*c)).count() < 3 {
+        return SpinKind::None;
+    }
+    let front = match piece.rotation {
+        RotationState::Spawn => [(-1, -1), (1, -1)],
+        RotationState::Right => [(1, -1), (1, 1)],
+        RotationState::Reverse => [(-1, 1), (1, 1)],
+        RotationState::Left => [(-1, -1), (-1, 1)],
+    };
+    if front.into_iter().all(occupied) || kick == UPGRADE_KICK {
+        SpinKind::TSpin
+    } else {
+        SpinKind::Mini
+    }
+}
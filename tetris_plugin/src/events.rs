@@ -13,9 +13,49 @@ pub struct PrintInfoTimer(pub Timer);
 pub struct SpawnEvent;
 
 #[derive(Debug, Clone, Event)]
-pub struct GameOverEvent;
+pub struct GameOverEvent(pub GameOverReason);
 
-#[derive(Debug, Copy, Clone, Event)]
+/// Why a game ended, the guideline top out conditions or the end of the game mode
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GameOverReason {
+    /// A new piece overlaps a block where it spawns
+    BlockOut,
+    /// A piece locked completely inside the hidden buffer rows
+    LockOut,
+    /// Blocks of a locked piece are left in the hidden buffer rows after the line clear
+    PartialLockOut,
+    /// Garbage pushed blocks or the active piece off the top of the map
+    TopOut,
+    /// The line goal of the game mode was reached
+    GoalReached,
+    /// The time limit of the game mode ran out
+    TimeUp,
+    /// The puzzle sequence ran out before the goal was met
+    OutOfPieces,
+}
+
+impl GameOverReason {
+    /// True if the game mode was completed instead of topping out
+    pub fn is_finished(&self) -> bool {
+        matches!(self, Self::GoalReached | Self::TimeUp)
+    }
+}
+
+impl std::fmt::Display for GameOverReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BlockOut => write!(f, "Block Out"),
+            Self::LockOut => write!(f, "Lock Out"),
+            Self::PartialLockOut => write!(f, "Partial Lock Out"),
+            Self::TopOut => write!(f, "Top Out"),
+            Self::GoalReached => write!(f, "Goal Reached"),
+            Self::TimeUp => write!(f, "Time Up"),
+            Self::OutOfPieces => write!(f, "Out Of Pieces"),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Event)]
 pub enum MoveEvent {
     Left,
     Right,
@@ -28,6 +68,23 @@ pub enum RotateEvent {
     CounterClockWise,
 }
 
+/// Moves the current tetromino as far as possible, used for an auto repeat rate of zero
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Event)]
+pub enum SlideEvent {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Event)]
+pub struct HoldEvent;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Event)]
+pub enum DropEvent {
+    Hard,
+    SoftStart,
+    SoftStop,
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Event)]
 pub enum GameCommand {
     Load,
@@ -39,3 +96,18 @@ pub enum GameCommand {
     DecreaseSpeed,
     TogglePause,
 }
+
+/// Sent by the save slot list of the pause menu
+#[derive(Debug, Clone, PartialEq, Eq, Event)]
+pub enum SlotCommand {
+    /// Saves the game to a new slot
+    Save,
+    Load(String),
+    Delete(String),
+    /// Writes the slot to a file in the directory
+    #[cfg(not(target_arch = "wasm32"))]
+    Export(String, std::path::PathBuf),
+    /// Reads a slot from the file, or every `.ron` file of the directory
+    #[cfg(not(target_arch = "wasm32"))]
+    Import(std::path::PathBuf),
+}
@@ -24,6 +24,14 @@ pub fn input(keys: Res<ButtonInput<KeyCode>>, mut game_command_event: EventWrite
         log::info!("loading detected");
         game_command_event.send(GameCommand::Load);
     }
+    if keys.just_pressed(KeyCode::PageUp) {
+        log::debug!("increase speed detected");
+        game_command_event.send(GameCommand::IncreaseSpeed);
+    }
+    if keys.just_pressed(KeyCode::PageDown) {
+        log::debug!("decrease speed detected");
+        game_command_event.send(GameCommand::DecreaseSpeed);
+    }
     if keys.just_pressed(KeyCode::KeyS) {
         log::info!("save detected");
         game_command_event.send(GameCommand::Save);
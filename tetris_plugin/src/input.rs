@@ -1,33 +1,227 @@
+use std::time::Duration;
+
 use bevy::log;
 use bevy::prelude::*;
 
+use crate::DropEvent;
+use crate::HandlingSettings;
+use crate::HoldEvent;
 use crate::MoveEvent;
 use crate::RotateEvent;
+use crate::SlideEvent;
+
+const ROTATE_KEYS: [KeyCode; 4] = [
+    KeyCode::ArrowUp,
+    KeyCode::KeyX,
+    KeyCode::ControlLeft,
+    KeyCode::KeyZ,
+];
+
+/// Horizontal movement produced by [`AutoShift`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Shift {
+    Move(MoveEvent),
+    Slide(SlideEvent),
+}
+
+/// Delayed auto shift of the held direction. A press moves once, after DAS the move repeats
+/// every ARR until the key is released.
+#[derive(Debug, Default)]
+pub(crate) struct AutoShift {
+    direction: Option<MoveEvent>,
+    /// time the direction is held, not counting DAS cuts
+    held: Duration,
+    repeats: u32,
+    cut: Duration,
+}
+
+impl AutoShift {
+    /// The direction which was pressed last wins if both are held
+    fn held_direction(&self, keys: &ButtonInput<KeyCode>) -> Option<MoveEvent> {
+        let left = keys.pressed(KeyCode::ArrowLeft);
+        let right = keys.pressed(KeyCode::ArrowRight);
+        match (left, right) {
+            (true, true) if keys.just_pressed(KeyCode::ArrowLeft) => Some(MoveEvent::Left),
+            (true, true) if keys.just_pressed(KeyCode::ArrowRight) => Some(MoveEvent::Right),
+            (true, true) => self.direction,
+            (true, false) => Some(MoveEvent::Left),
+            (false, true) => Some(MoveEvent::Right),
+            (false, false) => None,
+        }
+    }
+
+    pub(crate) fn update(
+        &mut self,
+        direction: Option<MoveEvent>,
+        rotated: bool,
+        delta: Duration,
+        settings: &HandlingSettings,
+    ) -> Vec<Shift> {
+        if direction != self.direction {
+            *self = Self {
+                direction,
+                ..Default::default()
+            };
+            return direction.map(Shift::Move).into_iter().collect();
+        }
+        let Some(direction) = direction else {
+            return vec![];
+        };
+        if rotated {
+            self.cut = settings.das_cut.as_duration();
+        }
+        if !self.cut.is_zero() {
+            self.cut = self.cut.saturating_sub(delta);
+            return vec![];
+        }
 
+        self.held += delta;
+        let das = settings.das.as_duration();
+        if self.held < das {
+            return vec![];
+        }
+        let arr = settings.arr.as_duration();
+        if arr.is_zero() {
+            let slide = match direction {
+                MoveEvent::Left => SlideEvent::Left,
+                _ => SlideEvent::Right,
+            };
+            return vec![Shift::Slide(slide)];
+        }
+        let repeats = 1 + ((self.held - das).as_nanos() / arr.as_nanos()) as u32;
+        let moves = repeats - self.repeats;
+        self.repeats = repeats;
+        vec![Shift::Move(direction); moves as usize]
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn input(
     keys: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    settings: Res<HandlingSettings>,
+    mut auto_shift: Local<AutoShift>,
     mut move_ewr: EventWriter<MoveEvent>,
+    mut slide_ewr: EventWriter<SlideEvent>,
     mut rotate_ewr: EventWriter<RotateEvent>,
+    mut hold_ewr: EventWriter<HoldEvent>,
+    mut drop_ewr: EventWriter<DropEvent>,
 ) {
-    if keys.just_pressed(KeyCode::ArrowLeft) {
-        log::debug!("move left");
-        move_ewr.send(MoveEvent::Left);
-    }
-    if keys.just_pressed(KeyCode::ArrowRight) {
-        log::debug!("move right");
-
-        move_ewr.send(MoveEvent::Right);
+    let direction = auto_shift.held_direction(&keys);
+    let rotated = keys.any_just_pressed(ROTATE_KEYS);
+    for shift in auto_shift.update(direction, rotated, time.delta(), &settings) {
+        log::debug!("{:?}", shift);
+        match shift {
+            Shift::Move(event) => {
+                move_ewr.send(event);
+            }
+            Shift::Slide(event) => {
+                slide_ewr.send(event);
+            }
+        }
     }
-    if keys.just_pressed(KeyCode::ArrowDown) {
+    if keys.any_just_pressed([KeyCode::ArrowUp, KeyCode::KeyX]) {
         log::debug!("rotate ClockWise");
         rotate_ewr.send(RotateEvent::ClockWise);
     }
-    if keys.just_pressed(KeyCode::ArrowUp) {
+    if keys.any_just_pressed([KeyCode::ControlLeft, KeyCode::KeyZ]) {
         log::debug!("rotate CounterClockWise");
         rotate_ewr.send(RotateEvent::CounterClockWise);
     }
     if keys.just_pressed(KeyCode::Space) {
-        log::debug!("move down");
-        move_ewr.send(MoveEvent::Down);
+        log::debug!("hard drop");
+        drop_ewr.send(DropEvent::Hard);
+    }
+    if keys.any_just_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        log::debug!("hold");
+        hold_ewr.send(HoldEvent);
+    }
+}
+
+/// Soft drop is held across pieces, so it must not miss a release while the next piece spawns
+pub fn soft_drop_input(keys: Res<ButtonInput<KeyCode>>, mut drop_ewr: EventWriter<DropEvent>) {
+    if keys.just_pressed(KeyCode::ArrowDown) {
+        log::debug!("soft drop");
+        drop_ewr.send(DropEvent::SoftStart);
+    }
+    if keys.just_released(KeyCode::ArrowDown) {
+        log::debug!("soft drop released");
+        drop_ewr.send(DropEvent::SoftStop);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HandlingDelay;
+
+    const FRAME: Duration = Duration::from_millis(10);
+
+    fn settings(arr: u32, das_cut: u32) -> HandlingSettings {
+        HandlingSettings {
+            das: HandlingDelay::Millis(50),
+            arr: HandlingDelay::Millis(arr),
+            das_cut: HandlingDelay::Millis(das_cut),
+            ..Default::default()
+        }
+    }
+
+    fn hold(uut: &mut AutoShift, frames: usize, settings: &HandlingSettings) -> Vec<Shift> {
+        (0..frames)
+            .flat_map(|_| uut.update(Some(MoveEvent::Left), false, FRAME, settings))
+            .collect()
+    }
+
+    #[test]
+    fn test_das_and_arr() {
+        let settings = settings(20, 0);
+        let mut uut = AutoShift::default();
+        assert_eq!(
+            hold(&mut uut, 1, &settings),
+            vec![Shift::Move(MoveEvent::Left)]
+        );
+        // charging for 50ms
+        assert_eq!(hold(&mut uut, 4, &settings), vec![]);
+        assert_eq!(
+            hold(&mut uut, 1, &settings),
+            vec![Shift::Move(MoveEvent::Left)]
+        );
+        // one move every 20ms
+        assert_eq!(hold(&mut uut, 4, &settings).len(), 2);
+        // releasing the key resets DAS
+        assert_eq!(uut.update(None, false, FRAME, &settings), vec![]);
+        assert_eq!(hold(&mut uut, 5, &settings).len(), 1);
+    }
+
+    #[test]
+    fn test_arr_zero_slides() {
+        let settings = settings(0, 0);
+        let mut uut = AutoShift::default();
+        hold(&mut uut, 5, &settings);
+        assert_eq!(
+            hold(&mut uut, 1, &settings),
+            vec![Shift::Slide(SlideEvent::Left)]
+        );
+    }
+
+    #[test]
+    fn test_das_cut_after_rotation() {
+        let settings = settings(10, 30);
+        let mut uut = AutoShift::default();
+        hold(&mut uut, 6, &settings);
+        assert_eq!(
+            uut.update(Some(MoveEvent::Left), true, FRAME, &settings),
+            vec![]
+        );
+        assert_eq!(hold(&mut uut, 2, &settings), vec![]);
+        assert_eq!(hold(&mut uut, 1, &settings).len(), 1);
+    }
+
+    #[test]
+    fn test_frames_delay() {
+        assert_eq!(
+            HandlingDelay::Frames(6).as_duration(),
+            Duration::from_millis(100)
+        );
     }
 }
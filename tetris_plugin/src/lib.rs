@@ -1,10 +1,11 @@
 mod components;
-pub(crate) mod utils;
 
 use bevy_pkv::PkvStore;
 pub(crate) use components::*;
 mod resources;
 pub use resources::*;
+mod engine;
+pub use engine::*;
 mod systems;
 use states::GameLogicState;
 use states::InGame;
@@ -23,7 +24,10 @@ use bevy::prelude::*;
 
 use states::GameStatus;
 
-pub struct TetrisPlugin;
+#[derive(Default)]
+pub struct TetrisPlugin {
+    pub rules: RuleOptions,
+}
 
 #[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
 enum TetrisSystemSet {
@@ -62,10 +66,20 @@ impl Plugin for TetrisPlugin {
                 .chain(),
         );
 
-        app.insert_resource(Time::<Fixed>::from_seconds(0.5));
+        // one engine tick per frame, gravity is given in cells per frame
+        app.insert_resource(Time::<Fixed>::from_hz(TICKS_PER_SECOND));
         app.insert_resource(TickCounter(0));
-        app.insert_resource(NextShape(rand::random()));
+        app.insert_resource(self.rules.clone());
+        app.init_resource::<PieceQueue>();
+        app.init_resource::<HoldPiece>();
+        app.init_resource::<ActiveModifiers>();
+        app.init_resource::<SaveSlots>();
+        #[cfg(not(target_arch = "wasm32"))]
+        app.init_resource::<SlotDirectory>();
+        app.init_resource::<HandlingSettings>();
+        app.init_resource::<Level>();
         app.insert_resource(PkvStore::new("bevy-tetris", "bevy-tetris"));
+        app.add_systems(Startup, systems::load_save_slots);
         // app.init_state::<GameStatus>();
         app.add_sub_state::<states::GameStatus>();
         app.add_sub_state::<states::GameLogicState>();
@@ -77,64 +91,85 @@ impl Plugin for TetrisPlugin {
 
         app.add_systems(OnEnter(InGame), (systems::sidebar, systems::sidebar_left));
         app.add_systems(OnEnter(GameStatus::Paused), systems::show_popup);
-        app.add_systems(OnExit(GameStatus::Paused), systems::hide_popup);
+        app.add_systems(
+            OnExit(GameStatus::Paused),
+            (systems::hide_popup, systems::hide_save_slot_menu),
+        );
         app.add_systems(
             Update,
             (
                 // input during spawning state can cause the map to be in an invalid state because spawning and move systems can run in parallel and mutate the map
                 input::input.run_if(in_state(GameLogicState::Ticking)),
+                (
+                    input::soft_drop_input,
+                    systems::apply_handling,
+                    systems::drop,
+                )
+                    .chain()
+                    .in_set(TetrisSystemSet::BeforeRound)
+                    .run_if(in_state(GameStatus::Running)),
                 systems::events_to_state,
                 game_command_handler::input,
+                systems::change_speed.run_if(in_state(GameStatus::Running)),
                 systems::update_block_sprites_colors.run_if(in_state(GameLogicState::Ticking)),
                 systems::update_block_sprites_translation.run_if(in_state(GameLogicState::Ticking)),
                 systems::gameover,
                 systems::load_and_save_warning.run_if(not(in_state(GameStatus::Paused))),
-                load_and_save.run_if(in_state(GameStatus::Paused)),
-                systems::animate,
-                // Before ticking, rotate and move blocks from user input
-                // Play error sound must be before rotate and move because it will use the wrong coordinates to predict collision
                 (
-                    systems::play_error_sound,
-                    systems::rotate,
-                    systems::move_current,
+                    systems::update_save_slot_menu,
+                    systems::save_slot_buttons,
+                    load_and_save,
                 )
+                    .chain()
+                    .run_if(in_state(GameStatus::Paused)),
+                systems::animate,
+                // Before ticking, rotate and move blocks from user input
+                (systems::rotate, systems::move_current, systems::hold)
                     .chain()
                     .in_set(TetrisSystemSet::BeforeRound)
                     .run_if(in_state(GameLogicState::Ticking)),
+                // Render everything the engine did during this frame, including the fixed update ticks
+                (
+                    systems::play_error_sound,
+                    (systems::show_callout, systems::expire_callouts),
+                    (systems::apply_game_events, systems::update_ghost).chain(),
+                    (
+                        systems::sync_engine,
+                        (
+                            systems::update_hud,
+                            systems::spawn_preview.run_if(resource_changed::<PieceQueue>),
+                            systems::update_hold.run_if(resource_changed::<HoldPiece>),
+                        ),
+                    )
+                        .chain(),
+                )
+                    .in_set(TetrisSystemSet::AfterRound)
+                    .run_if(in_state(InGame)),
             ),
         );
         app.add_systems(
             FixedUpdate,
             ((
-                (systems::spawn_tetromino, systems::spawn_preview)
-                    .chain()
-                    .run_if(in_state(GameLogicState::Spawning)),
-                // detect collision before moving blocks automatically, so user can move blocks in the last line
-                (systems::detect_collision)
-                    .in_set(TetrisSystemSet::BeforeRound)
-                    .run_if(in_state(GameLogicState::Ticking)),
-                (systems::tick_counter, systems::tick)
+                (systems::spawn_tetromino).run_if(in_state(GameLogicState::Spawning)),
+                // the engine locks a piece which could not fall during the previous tick, so user can move blocks in the last line
+                (systems::tick)
                     .in_set(TetrisSystemSet::Round)
                     .run_if(in_state(GameLogicState::Ticking)),
-                //  Remove line and score
-                (
-                    systems::convert_to_block,
-                    systems::delete_line,
-                    systems::score,
-                    systems::update_score,
-                )
-                    .chain()
-                    .run_if(in_state(GameLogicState::Cleaning)),
             )
                 .run_if(in_state(GameStatus::Running)),),
         );
         app.add_systems(OnEnter(GameStatus::Gameover), systems::cleanup_board);
         app.add_event::<SpawnEvent>()
             .add_event::<GameCommand>()
+            .add_event::<SlotCommand>()
             .add_event::<GameOverEvent>()
             .add_event::<RotateEvent>()
+            .add_event::<HoldEvent>()
+            .add_event::<DropEvent>()
             .add_event::<ScoreEvent>()
-            .add_event::<MoveEvent>();
+            .add_event::<GameEvent>()
+            .add_event::<MoveEvent>()
+            .add_event::<SlideEvent>();
 
         #[cfg(feature = "debug")]
         {
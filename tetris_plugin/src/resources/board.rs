@@ -6,6 +6,8 @@ use bevy::prelude::*;
 pub(crate) struct Board {
     pub bounds: Bounds2,
     pub tile_size: f32,
+    /// Map rows above the visible board, see [`crate::RuleOptions::hidden_rows`]
+    pub hidden_rows: u16,
     // pub current_tetromino_shape: Option<ShapeEntity>, // now a world resource
     #[deprecated] // use Query<Entity, With<TileMapRoot>> instead
     pub entity: Entity,
@@ -16,19 +18,22 @@ impl Board {
         let new_x: f32 = self.bounds.position.x
             + (coordinates.x as f32 * self.tile_size)
             + (self.tile_size / 2.0);
-        let new_y: f32 = -self.bounds.position.y
-            - (coordinates.y as f32 * self.tile_size)
-            - 0.5 * self.tile_size;
+        let row = coordinates.y as f32 - self.hidden_rows as f32;
+        let new_y: f32 = -self.bounds.position.y - (row * self.tile_size) - 0.5 * self.tile_size;
         (new_x, new_y)
     }
 
     pub fn calc_transform(&self, coordinates: &Coordinates) -> Transform {
-        let new_x: f32 = self.bounds.position.x
-            + (coordinates.x as f32 * self.tile_size)
-            + (self.tile_size / 2.0);
-        let new_y: f32 =
-            self.bounds.position.y + (coordinates.y as f32 * self.tile_size) + 0.5 * self.tile_size;
-
+        let (new_x, new_y) = self.calc_translation(coordinates);
         Transform::from_translation(Vec3::new(new_x, new_y, 50.0))
     }
+
+    /// Blocks in the hidden rows are not drawn
+    pub fn visibility(&self, coordinates: &Coordinates) -> Visibility {
+        if coordinates.y < self.hidden_rows {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        }
+    }
 }
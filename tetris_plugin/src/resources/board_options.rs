@@ -23,7 +23,7 @@ pub enum BoardPosition {
 // We use serde to allow saving option presets and loading them at runtime
 #[derive(Debug, Clone, Serialize, Deserialize, Resource)]
 pub struct BoardOptions {
-    /// Tile map size
+    /// Visible tile map size, the hidden rows of the [`crate::RuleOptions`] come on top
     pub map_size: (u16, u16),
     /// Board world position
     pub position: BoardPosition,
@@ -31,6 +31,8 @@ pub struct BoardOptions {
     pub tile_size: TileSize,
     /// Padding between tiles
     pub tile_padding: f32,
+    /// Show where the current tetromino lands
+    pub ghost_piece: bool,
 }
 
 impl Default for TileSize {
@@ -53,10 +55,29 @@ impl Default for BoardPosition {
 impl Default for BoardOptions {
     fn default() -> Self {
         Self {
-            map_size: (15, 15),
+            map_size: (10, 20),
             position: Default::default(),
             tile_size: Default::default(),
             tile_padding: 2.,
+            ghost_piece: true,
+        }
+    }
+}
+
+impl BoardOptions {
+    /// Options for a board where every map cell is `cell_size` tiles wide and high
+    pub fn scaled(&self, cell_size: u16) -> Self {
+        let factor = cell_size as f32;
+        Self {
+            map_size: (self.map_size.0 / cell_size, self.map_size.1 / cell_size),
+            tile_size: match self.tile_size {
+                TileSize::Fixed(size) => TileSize::Fixed(size * factor),
+                TileSize::Adaptive { min, max } => TileSize::Adaptive {
+                    min: min * factor,
+                    max: max * factor,
+                },
+            },
+            ..self.clone()
         }
     }
 }
@@ -0,0 +1,134 @@
+use std::{
+    fmt::{Display, Formatter},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Garbage rows a dig game keeps on the board while the goal is not reached
+pub const DIG_ROWS_ON_BOARD: u32 = 10;
+
+/// Values the sidebar shows during a game
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HudItem {
+    Score,
+    Level,
+    /// Cleared lines, with the line goal of the mode if it has one
+    Lines,
+    /// Time since the game started
+    Time,
+    /// Time until the time limit of the mode
+    TimeLeft,
+    /// Goal of the puzzle
+    Goal,
+    /// Pieces left in the puzzle sequence
+    PiecesLeft,
+}
+
+/// How a game is won or lost, selected in the [`crate::RuleOptions`]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GameMode {
+    /// Clear 150 lines while the gravity increases every level
+    #[default]
+    Marathon,
+    /// Clear 40 lines as fast as possible
+    Sprint,
+    /// Score as many points as possible in two minutes
+    Ultra,
+    /// Endless game without top out, the stack is cleared instead
+    Zen,
+    /// Clear `rows` garbage lines as fast as possible. The board is refilled with garbage until
+    /// the rest of the goal is on the board.
+    Dig {
+        rows: u32,
+        /// Chance in percent that the hole column changes from one garbage row to the next
+        messiness: u8,
+    },
+    /// Solve the [`crate::Puzzle`] of the rules with its piece sequence
+    Puzzle,
+}
+
+impl GameMode {
+    /// Modes offered in the mode selection
+    pub const ALL: [Self; 7] = [
+        Self::Marathon,
+        Self::Sprint,
+        Self::Ultra,
+        Self::Zen,
+        Self::dig(10),
+        Self::dig(18),
+        Self::dig(100),
+    ];
+
+    /// Dig mode where the hole column changes every other row on average
+    pub const fn dig(rows: u32) -> Self {
+        Self::Dig {
+            rows,
+            messiness: 50,
+        }
+    }
+
+    /// Lines which complete the game, only garbage lines count in dig games
+    pub fn line_goal(&self) -> Option<u32> {
+        match self {
+            Self::Marathon => Some(150),
+            Self::Sprint => Some(40),
+            Self::Ultra | Self::Zen | Self::Puzzle => None,
+            Self::Dig { rows, .. } => Some(*rows),
+        }
+    }
+
+    /// The completion time is the result of the game, not the score
+    pub fn is_race(&self) -> bool {
+        matches!(self, Self::Sprint | Self::Dig { .. })
+    }
+
+    /// Playing time after which the game is over
+    pub fn time_limit(&self) -> Option<Duration> {
+        match self {
+            Self::Ultra => Some(Duration::from_secs(120)),
+            _ => None,
+        }
+    }
+
+    /// False if block out, lock out and top out clear the stack instead of ending the game
+    pub fn tops_out(&self) -> bool {
+        *self != Self::Zen
+    }
+
+    pub fn hud(&self) -> &'static [HudItem] {
+        match self {
+            Self::Marathon => &[HudItem::Score, HudItem::Level, HudItem::Lines],
+            Self::Sprint => &[HudItem::Time, HudItem::Lines],
+            Self::Ultra => &[HudItem::TimeLeft, HudItem::Score, HudItem::Lines],
+            Self::Zen => &[HudItem::Score, HudItem::Lines],
+            Self::Dig { .. } => &[HudItem::Time, HudItem::Lines],
+            Self::Puzzle => &[HudItem::Goal, HudItem::PiecesLeft, HudItem::Lines],
+        }
+    }
+
+    /// One line for the mode selection
+    pub fn description(&self) -> String {
+        match self {
+            Self::Marathon => "Clear 150 lines".to_string(),
+            Self::Sprint => "Clear 40 lines as fast as possible".to_string(),
+            Self::Ultra => "Score attack for two minutes".to_string(),
+            Self::Zen => "Endless, no game over".to_string(),
+            Self::Dig { rows, .. } => format!("Dig through {rows} garbage lines"),
+            Self::Puzzle => "Meet the goal with the given pieces".to_string(),
+        }
+    }
+}
+
+impl Display for GameMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Marathon => write!(f, "Marathon"),
+            Self::Sprint => write!(f, "Sprint"),
+            Self::Ultra => write!(f, "Ultra"),
+            Self::Zen => write!(f, "Zen"),
+            Self::Dig { rows, .. } => write!(f, "Dig {rows}"),
+            Self::Puzzle => write!(f, "Puzzle"),
+        }
+    }
+}
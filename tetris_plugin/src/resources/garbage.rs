@@ -0,0 +1,146 @@
+use std::{collections::BTreeSet, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Coordinates, Map, Tile};
+
+/// Rising garbage of the survival mode, enabled in the [`crate::RuleOptions`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SurvivalOptions {
+    /// Time until the first garbage row
+    pub start_interval: Duration,
+    /// Shortest time between two garbage rows
+    pub min_interval: Duration,
+    /// Every garbage row multiplies the interval by this factor
+    pub speed_up: f32,
+}
+
+impl Default for SurvivalOptions {
+    fn default() -> Self {
+        Self {
+            start_interval: Duration::from_secs(8),
+            min_interval: Duration::from_secs(1),
+            speed_up: 0.95,
+        }
+    }
+}
+
+impl SurvivalOptions {
+    /// Time until the next garbage row after `rows` rows were added
+    pub fn interval(&self, rows: u32) -> Duration {
+        let interval = self
+            .start_interval
+            .mul_f32(self.speed_up.powi(rows.min(i32::MAX as u32) as i32));
+        interval.max(self.min_interval)
+    }
+}
+
+/// New garbage cells of a garbage row and the blocks it moved up
+pub type GarbageStep = (Vec<Coordinates>, Vec<(Coordinates, Coordinates)>);
+
+impl Map {
+    /// Moves all blocks one row up and fills the bottom row with garbage, except for the hole.
+    /// Walls never move, blocks below them stop and garbage only fills the free cells. Blocks in
+    /// the top row are pushed off the map. The current tetromino has to be taken off the map
+    /// before. Returns the new garbage cells and the moved blocks.
+    pub fn push_garbage_row(&mut self, hole: u16) -> GarbageStep {
+        let mut transitions = vec![];
+        for x in 0..self.width as u16 {
+            // from the top, so the cell above a block is free before the block moves into it
+            for y in 0..self.height as u16 {
+                let from = Coordinates { x, y };
+                if !matches!(self.get(&from), Some(tile) if tile.is_block()) {
+                    continue;
+                }
+                if y == 0 {
+                    self.insert(from, Tile::Empty);
+                    continue;
+                }
+                let to = Coordinates { x, y: y - 1 };
+                if self.get(&to) != Some(&Tile::Empty) {
+                    continue;
+                }
+                let tile = self
+                    .insert(from, Tile::Empty)
+                    .expect("coordinates must exist");
+                self.insert(to, tile);
+                transitions.push((from, to));
+            }
+        }
+        let bottom = self.height as u16 - 1;
+        let mut cells = vec![];
+        for x in (0..self.width as u16).filter(|x| *x != hole) {
+            let coordinates = (x, bottom).into();
+            if self.get(&coordinates) == Some(&Tile::Empty) {
+                self.insert(coordinates, Tile::Garbage);
+                cells.push(coordinates);
+            }
+        }
+        (cells, transitions)
+    }
+
+    /// Coordinates of all garbage blocks
+    pub fn garbage_cells(&self) -> BTreeSet<Coordinates> {
+        self.inner
+            .iter()
+            .filter(|(_, tile)| **tile == Tile::Garbage)
+            .map(|(c, _)| *c)
+            .collect()
+    }
+
+    /// Number of rows with at least one garbage block
+    pub fn garbage_rows(&self) -> u32 {
+        (0..self.height as u16)
+            .filter(|y| {
+                (0..self.width as u16).any(|x| self.get(&(x, *y).into()) == Some(&Tile::Garbage))
+            })
+            .count() as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ToMap;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_push_garbage_row() {
+        let mut map = vec!["xxxx", "xxox", "#x##"].to_map();
+        let (cells, _) = map.push_garbage_row(0);
+        assert_eq!(map, vec!["xxox", "#x##", "x###"].to_map());
+        let expected: Vec<Coordinates> = vec![(1, 2).into(), (2, 2).into(), (3, 2).into()];
+        assert_eq!(cells, expected);
+    }
+
+    #[test]
+    fn test_push_garbage_row_keeps_walls() {
+        let mut map = vec!["xxxx", "=oxx", "oxxx", "=xxx"].to_map();
+        let (cells, transitions) = map.push_garbage_row(3);
+        // the block below the upper wall stops on it
+        assert_eq!(map, vec!["xoxx", "=xxx", "oxxx", "=##x"].to_map());
+        let expected: Vec<Coordinates> = vec![(1, 3).into(), (2, 3).into()];
+        assert_eq!(cells, expected);
+        assert_eq!(transitions, vec![((1, 1).into(), (1, 0).into())]);
+        assert_eq!(map.get(&(0, 1).into()), Some(&Tile::Wall));
+        assert_eq!(map.get(&(0, 3).into()), Some(&Tile::Wall));
+    }
+
+    #[test]
+    fn test_garbage_rows() {
+        let map = vec!["xxxx", "o#xx", "oxxo", "##x#"].to_map();
+        assert_eq!(map.garbage_rows(), 2);
+    }
+
+    #[test]
+    fn test_interval_speeds_up() {
+        let options = SurvivalOptions {
+            start_interval: Duration::from_secs(4),
+            min_interval: Duration::from_secs(1),
+            speed_up: 0.5,
+        };
+        assert_eq!(options.interval(0), Duration::from_secs(4));
+        assert_eq!(options.interval(1), Duration::from_secs(2));
+        assert_eq!(options.interval(5), Duration::from_secs(1));
+    }
+}
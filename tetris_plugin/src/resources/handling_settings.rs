@@ -0,0 +1,48 @@
+use std::time::Duration;
+
+use bevy::prelude::Resource;
+use serde::{Deserialize, Serialize};
+
+use crate::TICKS_PER_SECOND;
+
+/// A delay given either in milliseconds or in frames of the engine tick rate
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HandlingDelay {
+    Millis(u32),
+    Frames(u32),
+}
+
+impl HandlingDelay {
+    pub fn as_duration(&self) -> Duration {
+        match self {
+            Self::Millis(ms) => Duration::from_millis(*ms as u64),
+            Self::Frames(frames) => {
+                Duration::from_nanos((*frames as f64 * 1e9 / TICKS_PER_SECOND).round() as u64)
+            }
+        }
+    }
+}
+
+/// Player handling preferences, independent of the game rules
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Resource)]
+pub struct HandlingSettings {
+    /// Delayed Auto Shift, time a direction is held before it repeats
+    pub das: HandlingDelay,
+    /// Auto Repeat Rate, time between repeated moves. Zero slides to the wall instantly
+    pub arr: HandlingDelay,
+    /// Auto repeat pauses for this long after a rotation. Zero disables the DAS cut
+    pub das_cut: HandlingDelay,
+    /// Gravity multiplier while soft drop is held
+    pub soft_drop_factor: f32,
+}
+
+impl Default for HandlingSettings {
+    fn default() -> Self {
+        Self {
+            das: HandlingDelay::Millis(167),
+            arr: HandlingDelay::Millis(33),
+            das_cut: HandlingDelay::Millis(0),
+            soft_drop_factor: 20.0,
+        }
+    }
+}
@@ -0,0 +1,11 @@
+use bevy::prelude::Resource;
+
+use crate::ShapeType;
+
+/// The shape in the hold slot, mirrored from the [`crate::GameEngine`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Resource)]
+pub struct HoldPiece {
+    pub shape_type: Option<ShapeType>,
+    /// Hold was used for the active piece and is available again after the next lock
+    pub used: bool,
+}
@@ -0,0 +1,28 @@
+use bevy::prelude::Resource;
+
+/// Cleared lines needed for the next level
+pub const LINES_PER_LEVEL: u32 = 10;
+
+/// Gravity in cells per frame, starting with level 1. The last entry is 20G, which drops a piece
+/// to the stack within a single frame, and applies to all higher levels.
+pub const GRAVITY_TABLE: [f32; 19] = [
+    0.01667, 0.02102, 0.02698, 0.03526, 0.04692, 0.06361, 0.08787, 0.1237, 0.1775, 0.2598, 0.3878,
+    0.5906, 0.9181, 1.4570, 2.3612, 3.9091, 6.6135, 11.4379, 20.0,
+];
+
+/// The current level, mirrored from the [`crate::GameEngine`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Resource)]
+pub struct Level(pub u32);
+
+impl Default for Level {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+impl Level {
+    pub fn gravity(&self) -> f32 {
+        let index = (self.0.max(1) as usize - 1).min(GRAVITY_TABLE.len() - 1);
+        GRAVITY_TABLE[index]
+    }
+}
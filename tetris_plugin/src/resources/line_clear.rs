@@ -0,0 +1,255 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Coordinates, Map, Tile};
+
+/// What happens to the blocks above cleared lines, selected in the [`crate::RuleOptions`]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LineClearGravity {
+    /// Every row moves down by the number of cleared rows below it, blocks may float
+    #[default]
+    Naive,
+    /// Orthogonally connected blocks fall as a unit until the group is supported
+    Sticky,
+    /// Every block falls until it is supported. Lines completed by falling blocks are cleared
+    /// as well, which can chain.
+    Cascade,
+}
+
+/// Lines removed by one clear step and the blocks moved afterwards, from the bottom to the top
+pub type ClearStep = (Vec<u16>, Vec<(Coordinates, Coordinates)>);
+
+impl Map {
+    /// Indices of all rows which are completely filled with blocks
+    pub fn full_lines(&self) -> Vec<u16> {
+        (0..self.height as u16)
+            .filter(|y| self.is_line_full(*y))
+            .collect()
+    }
+
+    /// Empties the given full lines and lets the blocks above fall. Returns one step per clear,
+    /// only [`LineClearGravity::Cascade`] produces more than one.
+    pub fn clear_lines(&mut self, lines: Vec<u16>, gravity: LineClearGravity) -> Vec<ClearStep> {
+        let mut steps = vec![];
+        let mut lines = lines;
+        while !lines.is_empty() {
+            self.set_lines_to_empty(lines.clone());
+            let transitions = match gravity {
+                LineClearGravity::Naive => self.shift_rows_down(&lines),
+                LineClearGravity::Sticky => self.settle(|map, block| map.connected(block)),
+                LineClearGravity::Cascade => self.settle(|_, block| BTreeSet::from([block])),
+            };
+            steps.push((lines, transitions));
+            if gravity != LineClearGravity::Cascade {
+                break;
+            }
+            lines = self.full_lines();
+        }
+        steps
+    }
+
+    /// Moves each block down by the number of `empty_lines` below it. Blocks stop early on walls.
+    pub(crate) fn shift_rows_down(
+        &mut self,
+        empty_lines: &[u16],
+    ) -> Vec<(Coordinates, Coordinates)> {
+        let mut blocks: Vec<Coordinates> =
+            self.get_block_coordinates().into_iter().copied().collect();
+        blocks.sort_by_key(|c| (std::cmp::Reverse(c.y), c.x));
+        let mut transitions = vec![];
+        for from in blocks {
+            let lines = empty_lines.iter().filter(|y| **y > from.y).count() as u16;
+            // the blocks below have moved already, anything left in the way is a wall or rests
+            // on one
+            let mut shift = 0;
+            while shift < lines
+                && self.get(&(from.x, from.y + shift + 1).into()) == Some(&Tile::Empty)
+            {
+                shift += 1;
+            }
+            if shift == 0 {
+                continue;
+            }
+            let to = Coordinates {
+                x: from.x,
+                y: from.y + shift,
+            };
+            let tile = self
+                .insert(from, Tile::Empty)
+                .expect("coordinates must exist");
+            let must_be_empty = self.insert(to, tile);
+            assert!(matches!(must_be_empty, Some(Tile::Empty)));
+            transitions.push((from, to));
+        }
+        transitions
+    }
+
+    /// Lets groups of blocks fall until nothing moves anymore. `group` returns the blocks which
+    /// fall together with the given one.
+    fn settle(
+        &mut self,
+        group: impl Fn(&Map, Coordinates) -> BTreeSet<Coordinates>,
+    ) -> Vec<(Coordinates, Coordinates)> {
+        // current position to original position of every moved block
+        let mut origins: BTreeMap<Coordinates, Coordinates> = BTreeMap::new();
+        loop {
+            let mut blocks: Vec<Coordinates> =
+                self.get_block_coordinates().into_iter().copied().collect();
+            // the lowest blocks fall first, so they can support the ones above
+            blocks.sort_by_key(|c| (std::cmp::Reverse(c.y), c.x));
+            let mut visited = BTreeSet::new();
+            let mut moved = false;
+            for block in blocks {
+                if visited.contains(&block) {
+                    continue;
+                }
+                let group = group(self, block);
+                visited.extend(group.iter().copied());
+                let distance = self.fall_distance(&group);
+                if distance == 0 {
+                    continue;
+                }
+                moved = true;
+                let tiles: Vec<(Coordinates, Tile)> = group
+                    .iter()
+                    .map(|c| (*c, self.insert(*c, Tile::Empty).expect("block exists")))
+                    .collect();
+                for (from, tile) in tiles {
+                    let to = Coordinates {
+                        x: from.x,
+                        y: from.y + distance,
+                    };
+                    self.insert(to, tile);
+                    let origin = origins.remove(&from).unwrap_or(from);
+                    origins.insert(to, origin);
+                }
+            }
+            if !moved {
+                break;
+            }
+        }
+        let mut transitions: Vec<(Coordinates, Coordinates)> = origins
+            .into_iter()
+            .filter(|(to, from)| to != from)
+            .map(|(to, from)| (from, to))
+            .collect();
+        transitions.sort_by_key(|(from, _)| (std::cmp::Reverse(from.y), from.x));
+        transitions
+    }
+
+    /// Rows the group can fall before it touches the floor or a block outside the group
+    fn fall_distance(&self, group: &BTreeSet<Coordinates>) -> u16 {
+        let mut distance = 0;
+        loop {
+            let free = group.iter().all(|c| {
+                let below = Coordinates {
+                    x: c.x,
+                    y: c.y + distance + 1,
+                };
+                group.contains(&below) || matches!(self.get(&below), Some(Tile::Empty))
+            });
+            if !free {
+                return distance;
+            }
+            distance += 1;
+        }
+    }
+
+    /// All blocks which are orthogonally connected to `start`
+    fn connected(&self, start: Coordinates) -> BTreeSet<Coordinates> {
+        let mut group = BTreeSet::from([start]);
+        let mut open = vec![start];
+        while let Some(c) = open.pop() {
+            let neighbours = [
+                (c.x.wrapping_sub(1), c.y),
+                (c.x + 1, c.y),
+                (c.x, c.y.wrapping_sub(1)),
+                (c.x, c.y + 1),
+            ];
+            for (x, y) in neighbours {
+                let neighbour = Coordinates { x, y };
+                if matches!(self.get(&neighbour), Some(tile) if tile.is_block())
+                    && group.insert(neighbour)
+                {
+                    open.push(neighbour);
+                }
+            }
+        }
+        group
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ToMap;
+    use pretty_assertions::assert_eq;
+
+    fn clear(input: Vec<&str>, gravity: LineClearGravity) -> (Map, Vec<ClearStep>) {
+        let mut map = input.to_map();
+        let lines = map.full_lines();
+        let steps = map.clear_lines(lines, gravity);
+        (map, steps)
+    }
+
+    #[test]
+    fn test_naive_non_adjacent_lines() {
+        let input = vec!["xoxx", "oooo", "xxox", "oooo", "oxxo"];
+        let (map, steps) = clear(input, LineClearGravity::Naive);
+        let expected = vec!["xxxx", "xxxx", "xoxx", "xxox", "oxxo"];
+        assert_eq!(map, expected.to_map());
+        assert_eq!(steps.len(), 1);
+        assert_eq!(
+            steps[0],
+            (
+                vec![1, 3],
+                vec![
+                    ((2, 2).into(), (2, 3).into()),
+                    ((1, 0).into(), (1, 2).into())
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn test_sticky_groups_fall_as_unit() {
+        let input = vec!["xoox", "xoxx", "oooo", "xxxo", "oxxo"];
+        let (map, _) = clear(input, LineClearGravity::Sticky);
+        // the group drops until its left column rests on the floor, its right block keeps
+        // floating above the gap
+        let expected = vec!["xxxx", "xxxx", "xxxx", "xooo", "ooxo"];
+        assert_eq!(map, expected.to_map());
+    }
+
+    #[test]
+    fn test_cascade_chain_clear() {
+        let input = vec!["xxxo", "ooox", "oooo", "oxoo"];
+        let (map, steps) = clear(input, LineClearGravity::Cascade);
+        // the falling blocks complete the bottom line, which is cleared as well
+        let expected = vec!["xxxx", "xxxx", "xxxx", "oxoo"];
+        assert_eq!(map, expected.to_map());
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[0].0, vec![2]);
+        assert_eq!(steps[1].0, vec![3]);
+    }
+
+    #[test]
+    fn test_walls_are_never_cleared() {
+        let input = vec!["xxxx", "oxxx", "oooo", "=ooo", "=xxo"];
+        let (map, steps) = clear(input, LineClearGravity::Naive);
+        // the block above the wall stops on it
+        let expected = vec!["xxxx", "xxxx", "oxxx", "=xxx", "=xxo"];
+        assert_eq!(map, expected.to_map());
+        assert_eq!(steps[0].0, vec![2, 3]);
+    }
+
+    #[test]
+    fn test_naive_blocks_do_not_fall_through_walls() {
+        let input = vec!["oxxx", "=xxx", "oooo", "oooo", "xxxo"];
+        let (map, steps) = clear(input, LineClearGravity::Naive);
+        let expected = vec!["oxxx", "=xxx", "xxxx", "xxxx", "xxxo"];
+        assert_eq!(map, expected.to_map());
+        assert_eq!(steps, vec![(vec![2, 3], vec![])]);
+    }
+}
@@ -7,14 +7,14 @@ use bevy::prelude::Resource;
 use colored::Colorize;
 
 use crate::{
-    components::Matrix, events::MoveEvent, Coordinates, RotateEvent, Shape, ShapeType, Tile,
-    TileBlueprint, Transitions,
+    components::Matrix, events::MoveEvent, Coordinates, Shape, Tile, TileBlueprint, Transitions,
+    GARBAGE_SYMBOL, WALL_SYMBOL,
 };
 
 pub type MapTile = Tile;
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Resource)]
-pub(crate) struct Map {
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Resource)]
+pub struct Map {
     pub(crate) inner: BTreeMap<Coordinates, MapTile>,
     pub width: usize,
     pub height: usize,
@@ -95,13 +95,13 @@ impl Map {
     //         .map(|(c, _)| c.clone())
     //         .collect()
     // }
-    // pub fn get_block_coordinates(&self) -> Vec<&Coordinates> {
-    //     self.inner
-    //         .iter()
-    //         .filter(|(_, t)| t.is_block())
-    //         .map(|(c, _)| c)
-    //         .collect()
-    // }
+    pub fn get_block_coordinates(&self) -> Vec<&Coordinates> {
+        self.inner
+            .iter()
+            .filter(|(_, t)| t.is_block())
+            .map(|(c, _)| c)
+            .collect()
+    }
     // pub fn is_empty(&self) -> bool {
     //     self.inner
     //         .iter()
@@ -124,22 +124,30 @@ impl Map {
     }
 
     pub fn detect_move_collision(&self, event: &MoveEvent) -> Option<CollisionDetection> {
+        let offset = match event {
+            &MoveEvent::Down => (0, 1),
+            &MoveEvent::Left => (-1, 0),
+            &MoveEvent::Right => (1, 0),
+        };
+        self.detect_offset_collision(offset)
+    }
+
+    /// Checks if the current tetromino could be moved by `offset` without hitting anything
+    pub fn detect_offset_collision(&self, offset: (i16, i16)) -> Option<CollisionDetection> {
         self.inner
             .iter()
             .filter(|(_, t)| t.is_moveable())
             .find_map(|(c, _tile)| {
-                let new_coordinates = match event {
-                    &MoveEvent::Down => *c + (0, 1),
-                    &MoveEvent::Left => *c + (-1, 0),
-                    &MoveEvent::Right => *c + (1, 0),
-                };
+                let new_coordinates = *c + offset;
 
                 if new_coordinates.y == self.height as u16 {
                     Some(CollisionDetection::Bottom)
                 } else {
                     match self.inner.get(&new_coordinates) {
                         Some(Tile::CurrentTetromino(_)) => None, // TODO optimize movement to move only required tiles
-                        Some(Tile::Block(_)) => Some(CollisionDetection::Block),
+                        Some(Tile::Block(_) | Tile::Garbage | Tile::Wall) => {
+                            Some(CollisionDetection::Block)
+                        }
                         Some(Tile::Empty) => None,
                         None => Some(CollisionDetection::OutOfBounds),
                     }
@@ -147,6 +155,22 @@ impl Map {
             })
     }
 
+    /// Number of rows the current tetromino can fall until it lands, 0 without a tetromino
+    pub fn drop_distance(&self) -> u16 {
+        if !self.inner.values().any(|t| t.is_moveable()) {
+            return 0;
+        }
+        let mut distance = 0;
+        while (distance as usize) < self.height
+            && self
+                .detect_offset_collision((0, distance as i16 + 1))
+                .is_none()
+        {
+            distance += 1;
+        }
+        distance
+    }
+
     pub fn detect_collision(&self) -> Option<CollisionDetection> {
         self.inner
             .iter()
@@ -158,7 +182,9 @@ impl Map {
                 } else {
                     match self.inner.get(&new_coordinates) {
                         Some(Tile::CurrentTetromino(_)) => None, // TODO optimize movement to move only required tiles
-                        Some(Tile::Block(_)) => Some(CollisionDetection::Block),
+                        Some(Tile::Block(_) | Tile::Garbage | Tile::Wall) => {
+                            Some(CollisionDetection::Block)
+                        }
                         Some(Tile::Empty) => None,
                         None => Some(CollisionDetection::OutOfBounds),
                     }
@@ -187,24 +213,32 @@ impl Map {
         }
     }
 
+    /// Adds empty rows on top, used for maps without the hidden rows above the visible board
+    pub fn with_hidden_rows(self, rows: u16) -> Self {
+        let mut map = Map::new(self.width, self.height + rows as usize);
+        for (c, tile) in self.inner {
+            map.inner.insert((c.x, c.y + rows).into(), tile);
+        }
+        map
+    }
+
     pub fn as_savegame_string(&self) -> String {
         let mut buffer = String::new();
         for (i, (_coord, tile)) in self.inner.iter().enumerate() {
             if i % self.width == 0 && i > 1 {
                 buffer.push('\n');
             }
-            let c = match tile {
-                &MapTile::Block(c) => c.to_string(),
-                &MapTile::CurrentTetromino(c) => c.to_ascii_uppercase().to_string(),
-                &MapTile::Empty => 'x'.to_string(),
-            };
-            buffer.push_str(&c);
+            buffer.push(tile.symbol());
         }
         buffer
     }
     pub fn as_tetris(&self) -> String {
         let mut buffer = String::new();
-        buffer.push_str("|0||1||2||3||4||5||6||7||8||9|\n");
+        // one digit per column, so the header is as wide as the rows
+        for x in 0..self.width {
+            buffer.push_str(&format!("|{}|", x % 10));
+        }
+        buffer.push('\n');
         use std::cmp::*;
         let mut vec: Vec<(&Coordinates, &Tile)> = self.inner.iter().collect();
         vec.sort_unstable_by(|a, b| a.0.y.cmp(&b.0.y).then(a.0.x.cmp(&b.0.x)));
@@ -216,6 +250,8 @@ impl Map {
             // buffer.push_str(&tile.to_string());
             let c = match tile {
                 &MapTile::Block(c) => c.to_string().red().to_string(),
+                &MapTile::Garbage => GARBAGE_SYMBOL.to_string().white().to_string(),
+                &MapTile::Wall => WALL_SYMBOL.to_string().blue().to_string(),
                 &MapTile::CurrentTetromino(c) => {
                     format!("{}", c.to_ascii_uppercase()).green().to_string()
                 }
@@ -234,7 +270,7 @@ impl Map {
             .iter()
             .map(|(pos, bp)| {
                 let tile = if bp == &TileBlueprint::CurrentTetromino {
-                    MapTile::CurrentTetromino(shape.shape_type.as_char())
+                    MapTile::CurrentTetromino(shape.symbol)
                 } else {
                     MapTile::Empty
                 };
@@ -263,7 +299,7 @@ impl Map {
         let mut res = vec![];
         self.inner
             .iter_mut()
-            .filter(|(c, _)| c.y == line)
+            .filter(|(c, t)| c.y == line && t != &&MapTile::Wall)
             .for_each(|(c, t)| {
                 assert!(t.is_block());
                 *t = MapTile::Empty;
@@ -273,86 +309,44 @@ impl Map {
         res
     }
 
+    /// Walls count as filled, but a line of walls only is never full
     pub(crate) fn is_line_full(&self, y: u16) -> bool {
-        let mut count = 0;
+        let mut blocks = 0;
+        let mut walls = 0;
         self.inner
             .iter()
             .filter(|(c, _)| c.y == y)
             .for_each(|(_, t)| {
-                if t == &MapTile::Empty {
-                    // TODO how to fail fast?
+                if t == &MapTile::Wall {
+                    walls += 1;
                 } else if t.is_block() {
-                    count += 1;
+                    blocks += 1;
                 }
             });
 
-        return count == self.width;
+        return blocks > 0 && blocks + walls == self.width;
     }
 
     /**
      * Returns a Vec of the lines that have to be deleted and the transitions of the blocks which have to be moved.
      * The transitions are sorted in a way that the blocks are moved from the bottom to the top.
+     * Each block moves down by the number of empty lines below it.
      */
     pub fn move_blocks_above_empty_lines(&mut self) -> Option<(Vec<u16>, Transitions)> {
-        let mut lines_with_blocks = vec![];
-        let mut lines_without_blocks = vec![];
-        for (c, t) in self.inner.iter() {
-            if t.is_block() {
-                lines_with_blocks.push(c.y);
-            }
-        }
-        lines_with_blocks.sort();
-        lines_with_blocks.dedup();
-        for i in 0..self.height {
-            if !lines_with_blocks.contains(&(i as u16)) {
-                lines_without_blocks.push(i as u16);
-            }
-        }
-
-        let mut lines_tuppels_old = vec![];
-        let mut lines_to_delete = vec![];
-        for y1 in &lines_with_blocks {
-            for y2 in &lines_without_blocks.clone() {
-                if y1 < y2 {
-                    lines_tuppels_old.push((y1.clone(), y2.clone()));
-                    lines_to_delete.push(y2.clone());
-                }
-            }
-        }
-
-        lines_to_delete.sort();
-        lines_to_delete.dedup();
-        if lines_tuppels_old.is_empty() {
+        let lines_with_blocks: Vec<u16> =
+            self.get_block_coordinates().iter().map(|c| c.y).collect();
+        let Some(top) = lines_with_blocks.iter().min() else {
+            return None;
+        };
+        let lines_to_delete: Vec<u16> = (*top..self.height as u16)
+            .filter(|y| !lines_with_blocks.contains(y))
+            .collect();
+        if lines_to_delete.is_empty() {
             return None;
         }
-
-        println!("lines_to_move {:?}", lines_tuppels_old);
-        lines_tuppels_old.reverse();
-        println!("lines_to_move {:?}", lines_tuppels_old);
         println!("lines_to_delete {:?}", lines_to_delete);
 
-        let mut transitions = vec![];
-
-        for (y_source, _deleted_line) in lines_tuppels_old.clone() {
-            for x in 0..self.width as u16 {
-                let from = (x, y_source).into();
-                let current = self.inner.get(&from);
-                if let Some(t) = current {
-                    if t.is_block() {
-                        let to = (x, y_source + lines_to_delete.len() as u16).into();
-                        let current = self.inner.insert(from.clone(), MapTile::Empty);
-                        let must_be_empty = self
-                            .inner
-                            .insert(to, current.expect("cooridinates must exist"));
-                        assert!(matches!(must_be_empty, Some(MapTile::Empty)));
-                        transitions.push((from, to))
-                    }
-                }
-            }
-        }
-
-        let transitions = Transitions(transitions);
-        lines_to_delete.sort();
+        let transitions = Transitions(self.shift_rows_down(&lines_to_delete));
         Some((lines_to_delete, transitions))
     }
     // fn move_blocks_above_line(&mut self, lines: Vec<u16>) -> Vec<(Coordinates, i16)> {
@@ -372,9 +366,148 @@ impl Map {
     //     old_blocks.into_iter().map(|(c, t, _)| (c, t)).collect()
     // }
 
-    pub(crate) fn from_str(text: &str) -> Self {
-        let lines: Vec<&str> = text.split('\n').collect();
-        lines.to_map()
+    /// Reads the map text format, see [`Map::parse_with_header`]
+    pub fn parse(text: &str) -> Result<Self, MapParseError> {
+        Self::parse_with_header(text).map(|(_, map)| map)
+    }
+
+    /// Reads one row per line in the cell format of [`Tile::from_symbol`], LF and CRLF line
+    /// endings are accepted. An optional header of `key: value` lines ends with a `---` line:
+    ///
+    /// ```text
+    /// version: 2
+    /// width: 4
+    /// height: 3
+    /// comment: one T and some garbage
+    /// ---
+    /// xTxx
+    /// TTT=
+    /// ##x=
+    /// ```
+    ///
+    /// Without a header the size is taken from the rows. With a height, missing rows are added
+    /// empty at the top.
+    pub fn parse_with_header(text: &str) -> Result<(MapHeader, Self), MapParseError> {
+        let mut lines = text.lines().enumerate().map(|(i, line)| (i + 1, line));
+        let mut header = MapHeader::default();
+        let has_header = text.lines().next().is_some_and(|line| line.contains(':'));
+        if has_header {
+            loop {
+                let Some((number, line)) = lines.next() else {
+                    let end = text.lines().count() + 1;
+                    return Err(MapParseError::new(
+                        end,
+                        1,
+                        MapParseErrorKind::MissingSeparator,
+                    ));
+                };
+                if line.trim() == "---" {
+                    break;
+                }
+                header.read(number, line)?;
+            }
+        }
+
+        let rows: Vec<(usize, Vec<char>)> = lines
+            .map(|(number, line)| (number, line.chars().collect()))
+            .collect();
+        let width = match (header.width, rows.first()) {
+            (Some(width), _) => width,
+            (None, Some((_, row))) if !row.is_empty() => row.len(),
+            (None, first) => {
+                let line = first.map_or(text.lines().count() + 1, |(number, _)| *number);
+                return Err(MapParseError::new(line, 1, MapParseErrorKind::Empty));
+            }
+        };
+        let height = header.height.unwrap_or(rows.len());
+        if let Some((number, _)) = rows.get(height) {
+            return Err(MapParseError::new(
+                *number,
+                1,
+                MapParseErrorKind::TooManyRows {
+                    expected: height,
+                    found: rows.len(),
+                },
+            ));
+        }
+
+        let mut map = Map::new(width, height);
+        let top = height - rows.len();
+        for (y, (number, row)) in rows.iter().enumerate() {
+            if row.len() != width {
+                return Err(MapParseError::new(
+                    *number,
+                    row.len().min(width) + 1,
+                    MapParseErrorKind::RowLength {
+                        expected: width,
+                        found: row.len(),
+                    },
+                ));
+            }
+            for (x, c) in row.iter().enumerate() {
+                let tile = Tile::from_symbol(*c).ok_or(MapParseError::new(
+                    *number,
+                    x + 1,
+                    MapParseErrorKind::UnknownCell(*c),
+                ))?;
+                map.insert((x as u16, (top + y) as u16).into(), tile);
+            }
+        }
+        Ok((header, map))
+    }
+
+    /// The map with a header for [`Map::parse_with_header`]
+    pub fn as_map_file(&self, comment: Option<&str>) -> String {
+        let mut text = format!(
+            "version: {MAP_FORMAT_VERSION}\nwidth: {}\nheight: {}\n",
+            self.width, self.height
+        );
+        if let Some(comment) = comment {
+            text.push_str(&format!("comment: {}\n", comment.replace('\n', " ")));
+        }
+        text.push_str("---\n");
+        text.push_str(&self.as_savegame_string());
+        text.push('\n');
+        text
+    }
+
+    /// Reads the output of [`Map::as_tetris`], without colours
+    pub fn parse_tetris(text: &str) -> Result<Self, MapParseError> {
+        let mut lines = text.lines().enumerate().map(|(i, line)| (i + 1, line));
+        let width = match lines.next() {
+            Some((_, header)) if !header.is_empty() => header.replace('|', "").chars().count(),
+            _ => return Err(MapParseError::new(1, 1, MapParseErrorKind::Empty)),
+        };
+        let rows: Vec<(usize, &str)> = lines.collect();
+        let mut map = Map::new(width, rows.len());
+        for (y, (number, line)) in rows.into_iter().enumerate() {
+            // every cell is written as `|c|`
+            let cells: Vec<char> = line.chars().skip(1).step_by(3).collect();
+            if cells.len() != width || line.chars().count() != width * 3 {
+                return Err(MapParseError::new(
+                    number,
+                    cells.len().min(width) * 3 + 1,
+                    MapParseErrorKind::RowLength {
+                        expected: width,
+                        found: cells.len(),
+                    },
+                ));
+            }
+            for (x, c) in cells.into_iter().enumerate() {
+                let tile = match c {
+                    ' ' => Some(Tile::Empty),
+                    'x' => None,
+                    c => Tile::from_symbol(c),
+                }
+                .ok_or(MapParseError::new(
+                    number,
+                    x * 3 + 2,
+                    MapParseErrorKind::UnknownCell(c),
+                ))?;
+                map.insert((x as u16, y as u16).into(), tile);
+            }
+        }
+        Ok(map)
     }
 
     // pub(crate) fn try_parse_shape(&self) -> Result<ShapeEntity, ()> {
@@ -414,87 +547,6 @@ impl Map {
     //         _ => Err(()),
     //     }
     // }
-    /// only call this if board has a shape
-    pub fn is_free(&self, direction: &RotateEvent) -> bool {
-        // if let Some(shape) = &self.current_tetromino_shape {
-        //     enum Check {
-        //         W,
-        //         S,
-        //         O,
-        //         N,
-        //         Inner,
-        //     }
-        //     let target = if shape.layout.width == shape.layout.height {
-        //         (Check::Inner, None, 0)
-        //     } else if shape.layout.width > shape.layout.height {
-        //         if direction == RotateEvent::ClockWise {
-        //             // xxxx      xoox
-        //             // xooo =>   xoox
-        //             // xooo      xoox
-        //             (Check::N, None, shape.layout.width - shape.layout.height)
-        //         } else {
-        //             // xooo =>   ooxx
-        //             // xooo      ooxx
-        //             // xxxx      ooxx
-        //             (
-        //                 Check::S,
-        //                 Some(Check::W),
-        //                 shape.layout.width - shape.layout.height,
-        //             )
-        //         }
-        //     } else {
-        //         if direction == RotateEvent::ClockWise {
-        //             // xxoo      ooox
-        //             // xxoo =>   ooox
-        //             // xxoo      xxxx
-        //             (Check::W, None, shape.layout.height - shape.layout.width)
-        //         } else {
-        //             // xoox      xooo
-        //             // xoox =>   xooo
-        //             // xoox      xxxx
-        //             (Check::O, None, shape.layout.height - shape.layout.width)
-        //         }
-        //     };
-
-        //     match target {
-        //         (Check::Inner, _, _) => {
-        //             for c in self.get_current_shape_coordinates() {
-        //                 if let Some(x) = self.map.get(&(*c + shape.anker + shape.position_on_board))
-        //                 {
-        //                     if let Tile::Block = x {
-        //                         return false; // Collision
-        //                     } else {
-        //                         continue;
-        //                     }
-        //                 } else {
-        //                     return false; // out of bounds
-        //                 }
-        //             }
-        //         }
-        //         (compass, _, diff) => {
-        //             let count = diff
-        //                 * match compass {
-        //                     Check::N => -1 ,
-        //                     Check::O,
-        //                     _ => 0
-        //                 };
-        //             for c in 0..count {
-        //                 if let Some(t) = self.map.get(&(*c + shape.anker + shape.position_on_board))
-        //                 {
-        //                     if let Tile::Block = t {
-        //                         return false; // Collision
-        //                     } else {
-        //                         continue;
-        //                     }
-        //                 } else {
-        //                     return false; // out of bounds
-        //                 }
-        //             }
-        //         }
-        //     }
-        // }
-        true
-    }
 }
 
 fn get_lines_south(y: &u16, lines: &Vec<u16>) -> u16 {
@@ -515,12 +567,13 @@ impl Debug for Map {
             if i % self.width == 0 && i > 1 {
                 writeln!(f, "\n------------------------------------")?;
             }
-            let t: char = match tile {
-                &MapTile::Block(c) => c,
-                &MapTile::CurrentTetromino(c) => c.to_ascii_uppercase(),
-                &MapTile::Empty => 'x',
-            };
-            write!(f, "{}({},{})|", format!("{}", t).green(), c.x, c.y)?;
+            write!(
+                f,
+                "{}({},{})|",
+                format!("{}", tile.symbol()).green(),
+                c.x,
+                c.y
+            )?;
         }
         writeln!(f, "\n------------------------------------")?;
         Ok(())
@@ -533,6 +586,122 @@ impl Into<Map> for Vec<&str> {
     }
 }
 
+/// Version written into the header of [`Map::as_map_file`]. Version 1 is the format without a
+/// header.
+pub const MAP_FORMAT_VERSION: u32 = 2;
+
+/// Metadata in front of the rows of a map file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MapHeader {
+    pub version: Option<u32>,
+    pub width: Option<usize>,
+    pub height: Option<usize>,
+    pub comment: Option<String>,
+}
+
+impl MapHeader {
+    fn read(&mut self, number: usize, line: &str) -> Result<(), MapParseError> {
+        let error = |column: usize, kind| MapParseError::new(number, column, kind);
+        let Some((key, value)) = line.split_once(':') else {
+            return Err(error(1, MapParseErrorKind::MissingSeparator));
+        };
+        // columns are counted in characters, like the cells
+        let value_column = line.chars().count() - value.trim_start().chars().count() + 1;
+        let value = value.trim();
+        let size = |value: &str| match value.parse::<usize>() {
+            Ok(size) if size > 0 && size <= u16::MAX as usize => Ok(size),
+            _ => Err(error(
+                value_column,
+                MapParseErrorKind::InvalidValue(value.to_string()),
+            )),
+        };
+        match key.trim() {
+            "version" => match value.parse::<u32>() {
+                Ok(version @ 1..=MAP_FORMAT_VERSION) => self.version = Some(version),
+                Ok(version) => {
+                    return Err(error(
+                        value_column,
+                        MapParseErrorKind::UnsupportedVersion(version),
+                    ))
+                }
+                Err(_) => {
+                    return Err(error(
+                        value_column,
+                        MapParseErrorKind::InvalidValue(value.to_string()),
+                    ))
+                }
+            },
+            "width" => self.width = Some(size(value)?),
+            "height" => self.height = Some(size(value)?),
+            "comment" => self.comment = Some(value.to_string()),
+            key => return Err(error(1, MapParseErrorKind::UnknownKey(key.to_string()))),
+        }
+        Ok(())
+    }
+}
+
+/// Why a map text could not be read, at a 1-based line and column
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapParseError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: MapParseErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MapParseErrorKind {
+    /// No rows and no width in the header
+    Empty,
+    UnknownCell(char),
+    RowLength {
+        expected: usize,
+        found: usize,
+    },
+    /// More rows than the height in the header
+    TooManyRows {
+        expected: usize,
+        found: usize,
+    },
+    /// A header line without `:`, or a header without the closing `---` line
+    MissingSeparator,
+    UnknownKey(String),
+    InvalidValue(String),
+    UnsupportedVersion(u32),
+}
+
+impl MapParseError {
+    pub fn new(line: usize, column: usize, kind: MapParseErrorKind) -> Self {
+        Self { line, column, kind }
+    }
+}
+
+impl Display for MapParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+        match &self.kind {
+            MapParseErrorKind::Empty => write!(f, "the map has no rows"),
+            MapParseErrorKind::UnknownCell(c) => write!(f, "{c:?} is not a cell"),
+            MapParseErrorKind::RowLength { expected, found } => {
+                write!(f, "the row has {found} cells instead of {expected}")
+            }
+            MapParseErrorKind::TooManyRows { expected, found } => {
+                write!(f, "the map has {found} rows instead of {expected}")
+            }
+            MapParseErrorKind::MissingSeparator => {
+                write!(f, "expected `key: value` or the closing `---`")
+            }
+            MapParseErrorKind::UnknownKey(key) => write!(f, "unknown header key {key:?}"),
+            MapParseErrorKind::InvalidValue(value) => write!(f, "invalid value {value:?}"),
+            MapParseErrorKind::UnsupportedVersion(version) => {
+                write!(f, "map format version {version} is not supported")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MapParseError {}
+
+/// Builds maps in tests, panics on invalid input
 pub(crate) trait ToMap {
     type Item: Sized;
     fn to_map(self) -> Self::Item;
@@ -541,132 +710,21 @@ pub(crate) trait ToMap {
 impl ToMap for Vec<&str> {
     type Item = Map;
     fn to_map(self: Self) -> Self::Item {
-        let len: usize = self.len();
-        let vec_vec = self
-            .into_iter()
-            .enumerate()
-            .map(|(index_y, line)| {
-                let tiles: Vec<(usize, MapTile)> = line
-                    .chars()
-                    .collect::<Vec<char>>()
-                    .into_iter()
-                    .enumerate()
-                    .map(|(index_x, c)| match c {
-                        c if c.is_ascii_uppercase() => {
-                            (index_x, MapTile::CurrentTetromino(c.to_ascii_lowercase()))
-                        }
-                        'x' => (index_x, MapTile::Empty),
-                        c => (index_x, MapTile::Block(c)),
-                        // forbidden_char => panic!("found a forbidden character: {forbidden_char}"),
-                    })
-                    .collect();
-
-                let inner_map = tiles
-                    .into_iter()
-                    .map(|(x, t)| {
-                        (
-                            Coordinates {
-                                x: x as u16,
-                                y: index_y as u16,
-                            },
-                            t,
-                        )
-                    })
-                    .collect::<Vec<(Coordinates, MapTile)>>();
-                inner_map
-            })
-            .collect::<Vec<Vec<(Coordinates, MapTile)>>>();
-
-        let height = vec_vec.len();
-        let width = vec_vec[0].len();
-        let invalid_len = vec_vec
-            .iter()
-            .find(|line_parse_result| line_parse_result.len() > width);
-        if invalid_len.is_some() {
-            panic!("a string is not as long as the first string in this vector")
-        }
-        assert_eq!(height, len);
-        let inner = vec_vec
-            .into_iter()
-            .flatten()
-            .collect::<BTreeMap<Coordinates, MapTile>>();
-
-        Map {
-            inner,
-            height,
-            width,
-        }
+        Map::parse(&self.join("\n")).unwrap_or_else(|e| panic!("invalid map: {e}"))
     }
 }
 impl ToMap for String {
     type Item = Map;
+    /// Reads the output of [`Map::as_tetris`]
     fn to_map(self: Self) -> Self::Item {
-        let mut lines: Vec<&str> = self.split('\n').collect();
-        let first_line: &str = lines.remove(0);
-        let width = first_line.replace('|', "").len();
-        let lines = lines
-            .into_iter()
-            .enumerate()
-            .map(|(index_y, line)| {
-                let tiles: Vec<(usize, MapTile)> = line
-                    .chars()
-                    .filter(|c| c != &'|')
-                    .collect::<Vec<char>>()
-                    .into_iter()
-                    .enumerate()
-                    .filter_map(|(index_x, c)| match c {
-                        ' ' => Some((index_x, MapTile::Empty)),
-                        c if c.is_ascii_uppercase() => {
-                            Some((index_x, MapTile::CurrentTetromino(c.to_ascii_lowercase())))
-                        }
-                        c if ShapeType::is_shape_type(c) => Some((index_x, MapTile::Block(c))),
-                        '\r' => panic!("IDIOT! SAVE FILES WITH LF INSTEAD OF CRLF"), // panic because enumeration index does not match from here on
-                        forbidden_char => panic!("found a forbidden character: {forbidden_char}"), // panic because enumeration index does not match from here on
-                    })
-                    .collect();
-
-                let inner_map = tiles
-                    .into_iter()
-                    .map(|(x, t)| {
-                        (
-                            Coordinates {
-                                x: x as u16,
-                                y: index_y as u16,
-                            },
-                            t,
-                        )
-                    })
-                    .collect::<Vec<(Coordinates, MapTile)>>();
-                inner_map
-            })
-            .collect::<Vec<Vec<(Coordinates, MapTile)>>>();
-
-        let height = lines.len();
-        let invalid_len = lines
-            .iter()
-            .find(|line_parse_result| line_parse_result.len() > width);
-        if invalid_len.is_some() {
-            panic!("a string is not as long as the first string in this vector")
-        }
-        assert_eq!(height, 22);
-        assert_eq!(width, 10);
-        let inner = lines
-            .into_iter()
-            .flatten()
-            .collect::<BTreeMap<Coordinates, MapTile>>();
-
-        Map {
-            inner,
-            height,
-            width,
-        }
+        Map::parse_tetris(&self).unwrap_or_else(|e| panic!("invalid map: {e}"))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Map;
-    use crate::{CollisionDetection, Coordinates, MapTile, Shape, ShapeType, Tile, ToMap};
+    use super::{Map, MAP_FORMAT_VERSION};
+    use crate::{CollisionDetection, Coordinates, MapTile, PieceSet, ShapeType, Tile, ToMap};
     use pretty_assertions::assert_eq;
     use std::{
         collections::BTreeMap,
@@ -804,6 +862,16 @@ mod tests {
         assert_eq!(Some(CollisionDetection::Block), collision);
     }
 
+    #[test]
+    fn test_drop_distance() {
+        let uut: Map = vec!["xOOxx", "xOOxx", "xxxxx", "xxxxx", "xxzxx"].to_map();
+        assert_eq!(uut.drop_distance(), 2);
+        let uut: Map = vec!["xxxxx", "xxxxx", "xxxxx", "xxxOO", "xxxOO"].to_map();
+        assert_eq!(uut.drop_distance(), 0);
+        let uut: Map = vec!["xxxxx", "xxxxx", "xxzxx"].to_map();
+        assert_eq!(uut.drop_distance(), 0);
+    }
+
     #[test]
     fn test_create_tiles() {
         let c1 = Map::create_tiles(5, 5)
@@ -822,23 +890,25 @@ mod tests {
 
     #[test]
     fn test_from_str() {
-        let map = Map::from_str(include_str!("map.txt"));
+        let map = Map::parse(include_str!("map.txt")).unwrap();
         assert_eq!(map.as_coordinates().len(), 220);
         assert_eq!(map.get_block_coordinates().len(), 32);
     }
     #[test]
     fn test_to_debug_file() {
-        let map = Map::from_str(include_str!("map.txt"));
+        let map = Map::parse(include_str!("map.txt")).unwrap();
         assert_eq!(map.as_coordinates().len(), 220);
         assert_eq!(map.get_block_coordinates().len(), 32);
-        let mut file = File::create("debug.txt").expect("file creation failed");
+        // written outside of the repository, so test runs leave the tree clean
+        let path = std::env::temp_dir().join("tetris-debug.txt");
+        let mut file = File::create(path).expect("file creation failed");
         file.write_all(map.to_string().as_bytes())
             .expect("writing to file failed");
     }
 
     #[test]
     fn test_to_tetris() {
-        let map = Map::from_str(include_str!("map.txt"));
+        let map = Map::parse(include_str!("map.txt")).unwrap();
         assert_eq!(map.as_coordinates().len(), 220);
         assert_eq!(map.get_block_coordinates().len(), 32);
         let res = map.as_tetris();
@@ -897,7 +967,7 @@ mod tests {
 | ||o||o||z||z||l||o||o||s||s|
 |j||o||o||l||l||l||o||o||t||s|
 |j||j||j||i||i||i||i||t||t||t|"#;
-        let map = Map::from_str(include_str!("map.txt"));
+        let map = Map::parse(include_str!("map.txt")).unwrap();
         let expected = String::to_map(input.to_string());
         // println!("{}", expected.to_string());
         assert_eq!(map, expected);
@@ -932,10 +1002,84 @@ mod tests {
         assert_eq!(map, expected);
     }
 
+    #[test]
+    fn test_tetris_format_any_size() {
+        let input = vec!["xxxxxxxxxxxx", "xxxxxxxxxxxT", "oxxxxxxxxTTT"];
+        let map = input.to_map();
+        let text = map.as_tetris();
+        assert!(text.starts_with("|0||1||2||3||4||5||6||7||8||9||0||1|\n"));
+        let parsed = String::to_map(text);
+        assert_eq!(parsed, map);
+    }
+
+    #[test]
+    fn test_parse_header_and_crlf() {
+        let text = "version: 2\r\nwidth: 4\r\nheight: 3\r\ncomment: a T\r\n---\r\nxTx=\r\nTTT#\r\n";
+        let (header, map) = Map::parse_with_header(text).unwrap();
+        assert_eq!(header.comment.as_deref(), Some("a T"));
+        assert_eq!(header.version, Some(MAP_FORMAT_VERSION));
+        assert_eq!(map, vec!["xxxx", "xTx=", "TTT#"].to_map());
+        assert_eq!(map.get(&(3, 1).into()), Some(&Tile::Wall));
+        assert_eq!(Map::parse(&map.as_map_file(Some("a T"))), Ok(map));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        use super::{MapParseError, MapParseErrorKind};
+        let error = |line, column, kind| Err(MapParseError::new(line, column, kind));
+        assert_eq!(
+            Map::parse("xxx\nx?x"),
+            error(2, 2, MapParseErrorKind::UnknownCell('?'))
+        );
+        assert_eq!(
+            Map::parse("xxx\r\nxx\r\n"),
+            error(
+                2,
+                3,
+                MapParseErrorKind::RowLength {
+                    expected: 3,
+                    found: 2
+                }
+            )
+        );
+        assert_eq!(
+            Map::parse("width: 3\nsize: 2\n---\nxxx"),
+            error(2, 1, MapParseErrorKind::UnknownKey("size".to_string()))
+        );
+        assert_eq!(
+            Map::parse("version: 3\n---\nxxx"),
+            error(1, 10, MapParseErrorKind::UnsupportedVersion(3))
+        );
+        assert_eq!(
+            Map::parse("height: 1\nwidth: 3\n---\nxxx\nooo"),
+            error(
+                5,
+                1,
+                MapParseErrorKind::TooManyRows {
+                    expected: 1,
+                    found: 2
+                }
+            )
+        );
+        assert_eq!(
+            Map::parse("width: 3\nxxx"),
+            error(2, 1, MapParseErrorKind::MissingSeparator)
+        );
+        assert_eq!(Map::parse(""), error(1, 1, MapParseErrorKind::Empty));
+        assert_eq!(Map::parse_tetris("|0||1|\n| ||?|").unwrap_err().column, 5);
+    }
+
+    #[test]
+    fn test_with_hidden_rows() {
+        let input = vec!["xxT", "TTT"];
+        let expected = vec!["xxx", "xxx", "xxT", "TTT"];
+        assert_eq!(input.to_map().with_hidden_rows(2), expected.to_map());
+    }
+
     #[test]
     fn test_to_savegame_file() {
         let input = include_str!("map.txt");
-        let map = Map::from_str(input);
+        let map = Map::parse(input).unwrap();
         assert_eq!(map.as_coordinates().len(), 220);
         assert_eq!(map.get_block_coordinates().len(), 32);
         // let mut file = File::create("foo.txt").expect("file creation failed");
@@ -950,7 +1094,7 @@ mod tests {
 
     #[test]
     fn test_spawn_shape() {
-        let shape = Shape::blueprint(ShapeType::Z);
+        let shape = PieceSet::tetrominoes().shape(ShapeType::Z);
 
         let mut map = Map::new(5, 5);
         map.spawn(&shape, &(1, 1).into());
@@ -992,6 +1136,26 @@ mod tests {
         map.insert(Coordinates { x: 2, y: 2 }, MapTile::CurrentTetromino('o'));
         map.insert(Coordinates { x: 3, y: 4 }, MapTile::Block('t'));
         let s = format!("{map}");
+        assert_eq!(
+            s,
+            String::from(
+                r#"|0||1||2||3||4|
+| || || || || |
+| || || || || |
+| || ||O|| || |
+| || || || || |
+| || || ||t|| |
+"#
+            )
+        )
+    }
+
+    #[test]
+    fn test_debug_fmt() {
+        let mut map = Map::empty_square(5);
+        map.insert(Coordinates { x: 2, y: 2 }, MapTile::CurrentTetromino('o'));
+        map.insert(Coordinates { x: 3, y: 4 }, MapTile::Block('t'));
+        let s = format!("{map:?}");
         assert_eq!(
             s,
             String::from(
@@ -1,5 +1,11 @@
 pub use board_options::*;
 mod board_options;
+pub use rule_options::*;
+mod rule_options;
+pub use game_mode::*;
+mod game_mode;
+pub use handling_settings::*;
+mod handling_settings;
 pub use board_assets::*;
 mod board_assets;
 pub use board::*;
@@ -8,15 +14,33 @@ pub use tile::*;
 mod tile;
 pub use shape::*;
 mod shape;
+pub use piece_set::*;
+mod piece_set;
+pub use srs::*;
+mod srs;
 pub use score::Score;
 mod score;
 pub use shape_queue::*;
 mod shape_queue;
+pub use hold::HoldPiece;
+mod hold;
 pub use map::*;
 mod map;
+pub use line_clear::*;
+mod line_clear;
+pub use garbage::*;
+mod garbage;
+pub use puzzle::*;
+mod puzzle;
+pub use modifier::*;
+mod modifier;
+pub use save_slots::*;
+mod save_slots;
 pub use transitions::*;
 mod transitions;
 pub use popup::PopupText;
 mod popup;
 mod tick;
+pub use level::*;
 pub use tick::*;
+mod level;
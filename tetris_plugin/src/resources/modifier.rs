@@ -0,0 +1,260 @@
+use std::{fmt::Debug, time::Duration};
+
+use bevy::{
+    ecs::system::EntityCommands,
+    prelude::{Alpha, Color, Resource},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{InputAction, ShapeType};
+
+/// What a block sprite shows, passed to [`Modifier::block_color`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum BlockState {
+    /// The active piece, its ghost and the pieces in the preview and hold slots
+    Active,
+    /// Part of the stack for the given time, `None` for garbage and the blocks of a loaded game
+    Locked(Option<Duration>),
+    /// Part of the board layout, modifiers should leave it as it is
+    Wall,
+}
+
+/// Changes how a game is played or shown. Any number of modifiers stack on any game mode, they
+/// are applied in the order of the [`crate::RuleOptions`].
+pub trait Modifier: Debug + Send + Sync {
+    /// Changes the player input before it reaches the engine
+    fn input(&self, action: InputAction) -> InputAction {
+        action
+    }
+
+    /// Called for every block sprite of a piece when it spawns
+    fn on_spawn(&self, _block: &mut EntityCommands, _shape_type: ShapeType) {}
+
+    /// Called for every block sprite of a piece when it locks into the stack
+    fn on_lock(&self, _block: &mut EntityCommands) {}
+
+    /// Colour of a block sprite, the colour of its piece, garbage or wall is passed in
+    fn block_color(&self, color: Color, _state: BlockState) -> Color {
+        color
+    }
+
+    /// Width and height of one map cell in board tiles
+    fn cell_size(&self) -> u16 {
+        1
+    }
+
+    /// Needed to clone the [`ActiveModifiers`]
+    fn box_clone(&self) -> Box<dyn Modifier>;
+}
+
+impl Clone for Box<dyn Modifier> {
+    fn clone(&self) -> Self {
+        self.box_clone()
+    }
+}
+
+/// Built-in modifiers, selected in the [`crate::RuleOptions`]
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum GameModifier {
+    /// The stack disappears as soon as a piece locks
+    Invisible,
+    /// Locked blocks fade out over the given time
+    Fading(Duration),
+    /// Every map cell is drawn as 2x2 tiles, so the map has half the width and height
+    Big,
+    /// Left and right as well as the rotation directions are swapped
+    Mirror,
+    /// All pieces have the same colour
+    Monochrome,
+}
+
+impl GameModifier {
+    /// Parses the lowercase name of a modifier, `fading` fades out in five seconds
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "invisible" => Some(Self::Invisible),
+            "fading" => Some(Self::Fading(Duration::from_secs(5))),
+            "big" => Some(Self::Big),
+            "mirror" => Some(Self::Mirror),
+            "monochrome" => Some(Self::Monochrome),
+            _ => None,
+        }
+    }
+
+    pub fn modifier(&self) -> Box<dyn Modifier> {
+        match self {
+            Self::Invisible => Box::new(Invisible),
+            Self::Fading(duration) => Box::new(Fading(*duration)),
+            Self::Big => Box::new(Big),
+            Self::Mirror => Box::new(Mirror),
+            Self::Monochrome => Box::new(Monochrome),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Invisible;
+
+impl Modifier for Invisible {
+    fn block_color(&self, color: Color, state: BlockState) -> Color {
+        match state {
+            BlockState::Active | BlockState::Wall => color,
+            BlockState::Locked(_) => color.with_alpha(0.0),
+        }
+    }
+
+    fn box_clone(&self) -> Box<dyn Modifier> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Fading(pub Duration);
+
+impl Modifier for Fading {
+    fn block_color(&self, color: Color, state: BlockState) -> Color {
+        match state {
+            BlockState::Active | BlockState::Wall => color,
+            BlockState::Locked(Some(time)) if time < self.0 => {
+                let alpha = color.alpha() * (1.0 - time.as_secs_f32() / self.0.as_secs_f32());
+                color.with_alpha(alpha)
+            }
+            BlockState::Locked(_) => color.with_alpha(0.0),
+        }
+    }
+
+    fn box_clone(&self) -> Box<dyn Modifier> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Big;
+
+impl Modifier for Big {
+    fn cell_size(&self) -> u16 {
+        2
+    }
+
+    fn box_clone(&self) -> Box<dyn Modifier> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Mirror;
+
+impl Modifier for Mirror {
+    fn input(&self, action: InputAction) -> InputAction {
+        match action {
+            InputAction::MoveLeft => InputAction::MoveRight,
+            InputAction::MoveRight => InputAction::MoveLeft,
+            InputAction::SlideLeft => InputAction::SlideRight,
+            InputAction::SlideRight => InputAction::SlideLeft,
+            InputAction::RotateClockWise => InputAction::RotateCounterClockWise,
+            InputAction::RotateCounterClockWise => InputAction::RotateClockWise,
+            action => action,
+        }
+    }
+
+    fn box_clone(&self) -> Box<dyn Modifier> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Monochrome;
+
+impl Modifier for Monochrome {
+    fn block_color(&self, color: Color, state: BlockState) -> Color {
+        match state {
+            BlockState::Wall => color,
+            _ => Color::srgb(0.8, 0.8, 0.8).with_alpha(color.alpha()),
+        }
+    }
+
+    fn box_clone(&self) -> Box<dyn Modifier> {
+        Box::new(self.clone())
+    }
+}
+
+/// The modifiers of the running game, built from the [`crate::RuleOptions`] when the board is
+/// created
+#[derive(Debug, Clone, Default, Resource)]
+pub struct ActiveModifiers(pub Vec<Box<dyn Modifier>>);
+
+impl ActiveModifiers {
+    pub fn new(modifiers: &[GameModifier]) -> Self {
+        Self(modifiers.iter().map(GameModifier::modifier).collect())
+    }
+
+    pub fn input(&self, action: InputAction) -> InputAction {
+        self.0.iter().fold(action, |action, m| m.input(action))
+    }
+
+    pub fn on_spawn(&self, block: &mut EntityCommands, shape_type: ShapeType) {
+        self.0.iter().for_each(|m| m.on_spawn(block, shape_type));
+    }
+
+    pub fn on_lock(&self, block: &mut EntityCommands) {
+        self.0.iter().for_each(|m| m.on_lock(block));
+    }
+
+    pub fn block_color(&self, color: Color, state: BlockState) -> Color {
+        self.0
+            .iter()
+            .fold(color, |color, m| m.block_color(color, state))
+    }
+
+    /// The largest cell size of all modifiers
+    pub fn cell_size(&self) -> u16 {
+        self.0.iter().map(|m| m.cell_size()).max().unwrap_or(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_modifiers_stack() {
+        let modifiers = ActiveModifiers::new(&[
+            GameModifier::Monochrome,
+            GameModifier::Fading(Duration::from_secs(2)),
+            GameModifier::Mirror,
+            GameModifier::Mirror,
+        ]);
+        let red = Color::srgb(1.0, 0.0, 0.0);
+        let locked = BlockState::Locked(Some(Duration::from_secs(1)));
+        assert_eq!(
+            modifiers.block_color(red, locked),
+            Color::srgba(0.8, 0.8, 0.8, 0.5)
+        );
+        assert_eq!(
+            modifiers.block_color(red, BlockState::Locked(None)).alpha(),
+            0.0
+        );
+        let invisible = ActiveModifiers::new(&[GameModifier::Invisible, GameModifier::Monochrome]);
+        for modifiers in [&modifiers, &invisible] {
+            assert_eq!(modifiers.block_color(red, BlockState::Wall), red);
+        }
+        assert_eq!(
+            modifiers.input(InputAction::MoveLeft),
+            InputAction::MoveLeft
+        );
+        assert_eq!(modifiers.cell_size(), 1);
+    }
+
+    #[test]
+    fn test_mirror_and_big() {
+        let modifiers = ActiveModifiers::new(&[GameModifier::Mirror, GameModifier::Big]);
+        assert_eq!(
+            modifiers.input(InputAction::RotateClockWise),
+            InputAction::RotateCounterClockWise
+        );
+        assert_eq!(modifiers.input(InputAction::Hold), InputAction::Hold);
+        assert_eq!(modifiers.cell_size(), 2);
+        assert_eq!(GameModifier::from_name("big"), Some(GameModifier::Big));
+        assert_eq!(GameModifier::from_name("tiny"), None);
+    }
+}
@@ -0,0 +1,352 @@
+use std::fmt::{Display, Formatter};
+
+use bevy::prelude::Color;
+use serde::{Deserialize, Serialize};
+
+use crate::{Kicks, RotationState, Shape, ShapePosition, ShapeType};
+
+/// One piece of a [`PieceSet`], e.g. loaded from a RON or JSON file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PieceDefinition {
+    pub name: String,
+    /// Lowercase character of the locked blocks in save files, the active piece is written in
+    /// uppercase
+    pub symbol: char,
+    /// sRGB colour, every channel from 0.0 to 1.0
+    pub color: [f32; 3],
+    /// Rows of the square rotation box in spawn orientation, `#` marks a block
+    pub cells: Vec<String>,
+    /// Rotation states R, 2 and L in the same format as the cells. Empty to rotate the cells
+    /// clockwise inside the rotation box.
+    #[serde(default)]
+    pub rotations: Vec<Vec<String>>,
+    pub kicks: Kicks,
+    /// T-spins are detected with the 3-corner rule, needs a 3x3 rotation box
+    #[serde(default)]
+    pub t_spin: bool,
+}
+
+impl PieceDefinition {
+    /// Width and height of the rotation box
+    pub fn size(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn color(&self) -> Color {
+        let [r, g, b] = self.color;
+        Color::srgb(r, g, b)
+    }
+
+    /// Positions of the blocks inside the rotation box in the given rotation state
+    pub fn blocks(&self, rotation: RotationState) -> Vec<ShapePosition> {
+        match rotation.index() {
+            0 => blocks(&self.cells),
+            index if !self.rotations.is_empty() => blocks(&self.rotations[index - 1]),
+            index => {
+                let n = self.size() as i16;
+                blocks(&self.cells)
+                    .into_iter()
+                    .map(|pos| {
+                        (0..index).fold(pos, |p, _| ShapePosition {
+                            x: n - 1 - p.y,
+                            y: p.x,
+                        })
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    fn validate(&self) -> Result<(), PieceSetError> {
+        let invalid =
+            |reason: &str| Err(PieceSetError::Invalid(format!("{}: {reason}", self.name)));
+        if !self.symbol.is_ascii_lowercase() || self.symbol == 'x' {
+            return invalid("the symbol must be a lowercase letter other than x");
+        }
+        let size = self.size();
+        let states = std::iter::once(&self.cells).chain(self.rotations.iter());
+        for rows in states {
+            if rows.len() != size || rows.iter().any(|row| row.chars().count() != size) {
+                return invalid("the rotation box must be square");
+            }
+        }
+        if !self.rotations.is_empty() && self.rotations.len() != 3 {
+            return invalid("give either no rotations or the three states R, 2 and L");
+        }
+        let count = self.blocks(RotationState::Spawn).len();
+        if count == 0 {
+            return invalid("a piece needs at least one block");
+        }
+        if RotationState::ALL
+            .into_iter()
+            .any(|rotation| self.blocks(rotation).len() != count)
+        {
+            return invalid("every rotation state needs the same number of blocks");
+        }
+        if self.t_spin && size != 3 {
+            return invalid("T-spin detection needs a 3x3 rotation box");
+        }
+        self.kicks.validate().or_else(|reason| invalid(&reason))
+    }
+}
+
+/// Block positions of the rows, sorted column by column
+fn blocks(rows: &[String]) -> Vec<ShapePosition> {
+    let mut blocks: Vec<ShapePosition> = rows
+        .iter()
+        .enumerate()
+        .flat_map(|(y, row)| {
+            row.chars()
+                .enumerate()
+                .filter(|(_, c)| *c == '#')
+                .map(move |(x, _)| (x as i16, y as i16).into())
+        })
+        .collect();
+    blocks.sort();
+    blocks
+}
+
+/// The pieces of a game, selected in the [`crate::RuleOptions`]. A [`ShapeType`] is the index of
+/// a piece in this set.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PieceSet {
+    pub name: String,
+    pub pieces: Vec<PieceDefinition>,
+}
+
+impl Default for PieceSet {
+    fn default() -> Self {
+        Self::tetrominoes()
+    }
+}
+
+impl PieceSet {
+    /// The seven guideline tetrominoes with SRS kicks, in the order of the [`ShapeType`]
+    /// constants
+    pub fn tetrominoes() -> Self {
+        let piece = |name: &str, color: [f32; 3], rows: &[&str], kicks: Kicks| PieceDefinition {
+            name: name.to_string(),
+            symbol: name.to_ascii_lowercase().chars().next().expect("not empty"),
+            color,
+            cells: rows.iter().map(|row| row.to_string()).collect(),
+            rotations: vec![],
+            kicks,
+            t_spin: name == "T",
+        };
+        Self {
+            name: "Tetrominoes".to_string(),
+            pieces: vec![
+                piece(
+                    "I",
+                    [0.0, 0.7, 0.7],
+                    &["....", "####", "....", "...."],
+                    Kicks::SrsI,
+                ),
+                piece("O", [0.7, 0.7, 0.0], &["##", "##"], Kicks::None),
+                piece("T", [0.7, 0.0, 0.7], &[".#.", "###", "..."], Kicks::Srs),
+                piece("S", [0.0, 0.7, 0.0], &[".##", "##.", "..."], Kicks::Srs),
+                piece("Z", [0.7, 0.0, 0.0], &["##.", ".##", "..."], Kicks::Srs),
+                piece("L", [0.0, 0.0, 0.7], &["..#", "###", "..."], Kicks::Srs),
+                piece("J", [0.9, 0.25, 0.0], &["#..", "###", "..."], Kicks::Srs),
+            ],
+        }
+    }
+
+    pub fn from_ron(text: &str) -> Result<Self, PieceSetError> {
+        let set: Self = ron::from_str(text).map_err(PieceSetError::Ron)?;
+        set.validate()?;
+        Ok(set)
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, PieceSetError> {
+        let set: Self = serde_json::from_str(text).map_err(PieceSetError::Json)?;
+        set.validate()?;
+        Ok(set)
+    }
+
+    /// Reads a `.ron` or `.json` file
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self, PieceSetError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(PieceSetError::Io)?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("ron") => Self::from_ron(&text),
+            Some("json") => Self::from_json(&text),
+            _ => Err(PieceSetError::Invalid(format!(
+                "{} is neither a .ron nor a .json file",
+                path.display()
+            ))),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), PieceSetError> {
+        if self.pieces.is_empty() || self.pieces.len() > u8::MAX as usize {
+            return Err(PieceSetError::Invalid(format!(
+                "{}: a set needs between 1 and 255 pieces",
+                self.name
+            )));
+        }
+        for (i, piece) in self.pieces.iter().enumerate() {
+            piece.validate()?;
+            if self.pieces[..i].iter().any(|p| p.symbol == piece.symbol) {
+                return Err(PieceSetError::Invalid(format!(
+                    "{}: the symbol {} is used twice",
+                    self.name, piece.symbol
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// All shapes of the set, in the order of the definitions
+    pub fn shape_types(&self) -> Vec<ShapeType> {
+        (0..self.pieces.len() as u8).map(ShapeType).collect()
+    }
+
+    pub fn get(&self, shape_type: ShapeType) -> &PieceDefinition {
+        &self.pieces[shape_type.0 as usize]
+    }
+
+    /// The shape written with the lowercase or uppercase character in save files
+    pub fn shape_type(&self, symbol: char) -> Option<ShapeType> {
+        let symbol = symbol.to_ascii_lowercase();
+        self.pieces
+            .iter()
+            .position(|p| p.symbol == symbol)
+            .map(|index| ShapeType(index as u8))
+    }
+
+    pub fn symbol(&self, shape_type: ShapeType) -> char {
+        self.get(shape_type).symbol
+    }
+
+    pub fn color(&self, shape_type: ShapeType) -> Color {
+        self.get(shape_type).color()
+    }
+
+    /// The spawn orientation of a piece as a [`Shape`]
+    pub fn shape(&self, shape_type: ShapeType) -> Shape {
+        let piece = self.get(shape_type);
+        let rows: Vec<&str> = piece.cells.iter().map(String::as_str).collect();
+        Shape::from_rows(shape_type, piece.symbol, &rows)
+    }
+}
+
+#[derive(Debug)]
+pub enum PieceSetError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+    Json(serde_json::Error),
+    Invalid(String),
+}
+
+impl Display for PieceSetError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "cannot read piece set: {e}"),
+            Self::Ron(e) => write!(f, "invalid RON piece set: {e}"),
+            Self::Json(e) => write!(f, "invalid JSON piece set: {e}"),
+            Self::Invalid(reason) => write!(f, "invalid piece set {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for PieceSetError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn positions(piece: &PieceDefinition, rotation: RotationState) -> Vec<(i16, i16)> {
+        piece.blocks(rotation).into_iter().map(Into::into).collect()
+    }
+
+    #[test]
+    fn test_tetrominoes() {
+        let set = PieceSet::tetrominoes();
+        set.validate().unwrap();
+        for shape_type in set.shape_types() {
+            for rotation in RotationState::ALL {
+                assert_eq!(set.get(shape_type).blocks(rotation).len(), 4);
+            }
+        }
+        assert_eq!(set.shape_type('T'), Some(ShapeType::T));
+        assert_eq!(set.symbol(ShapeType::J), 'j');
+        assert_eq!(set.shape_type('q'), None);
+    }
+
+    #[test]
+    fn test_i_shape_rotation_states() {
+        let i = PieceSet::tetrominoes().get(ShapeType::I).clone();
+        assert_eq!(
+            positions(&i, RotationState::Right),
+            vec![(2, 0), (2, 1), (2, 2), (2, 3)]
+        );
+        assert_eq!(
+            positions(&i, RotationState::Reverse),
+            vec![(3, 2), (2, 2), (1, 2), (0, 2)]
+        );
+    }
+
+    #[test]
+    fn test_explicit_rotations() {
+        let text = r###"(
+            name: "Domino",
+            pieces: [(
+                name: "D",
+                symbol: 'd',
+                color: (1.0, 1.0, 1.0),
+                cells: ["##", ".."],
+                rotations: [["#.", "#."], ["##", ".."], ["#.", "#."]],
+                kicks: None,
+            )],
+        )"###;
+        let set = PieceSet::from_ron(text).unwrap();
+        let domino = set.get(ShapeType(0));
+        assert_eq!(
+            positions(domino, RotationState::Right),
+            vec![(0, 0), (0, 1)]
+        );
+        assert!(!domino.t_spin);
+    }
+
+    #[test]
+    fn test_pentominoes_asset() {
+        let set =
+            PieceSet::from_ron(include_str!("../../../assets/pieces/pentominoes.ron")).unwrap();
+        assert_eq!(set.pieces.len(), 18);
+        for shape_type in set.shape_types() {
+            for rotation in RotationState::ALL {
+                assert_eq!(set.get(shape_type).blocks(rotation).len(), 5);
+            }
+        }
+    }
+
+    #[test]
+    fn test_trominoes_asset() {
+        let set =
+            PieceSet::from_json(include_str!("../../../assets/pieces/trominoes.json")).unwrap();
+        assert_eq!(set.pieces.len(), 2);
+        assert_eq!(set.shape_type('v'), Some(ShapeType(1)));
+    }
+
+    #[test]
+    fn test_invalid_sets() {
+        let mut set = PieceSet::tetrominoes();
+        set.pieces[1].symbol = 'i';
+        assert!(matches!(set.validate(), Err(PieceSetError::Invalid(_))));
+
+        let mut set = PieceSet::tetrominoes();
+        set.pieces[0].cells.pop();
+        assert!(matches!(set.validate(), Err(PieceSetError::Invalid(_))));
+
+        assert!(matches!(
+            PieceSet::from_json("{\"name\": \"empty\", \"pieces\": []}"),
+            Err(PieceSetError::Invalid(_))
+        ));
+        assert!(matches!(
+            PieceSet::from_ron("(name: \"broken\""),
+            Err(PieceSetError::Ron(_))
+        ));
+    }
+}
@@ -0,0 +1,203 @@
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ClearResult, Map, MapParseError, PieceSet, ShapeType, SpinKind, Tile};
+
+/// What a puzzle asks for
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PuzzleGoal {
+    /// Clear at least this many lines in total
+    Lines(u32),
+    /// Leave an empty board after a clear
+    PerfectClear,
+    /// A T-spin clearing exactly this many lines, e.g. 2 for a T-spin double
+    TSpin(u32),
+}
+
+impl PuzzleGoal {
+    /// Checks the goal after a lock, `lines` are all lines cleared so far
+    pub fn is_met(&self, lines: u32, result: &ClearResult) -> bool {
+        match self {
+            Self::Lines(goal) => lines >= *goal,
+            Self::PerfectClear => result.perfect_clear,
+            Self::TSpin(goal) => result.spin == SpinKind::TSpin && result.lines == *goal,
+        }
+    }
+}
+
+impl Display for PuzzleGoal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Lines(1) => write!(f, "Clear 1 line"),
+            Self::Lines(lines) => write!(f, "Clear {lines} lines"),
+            Self::PerfectClear => write!(f, "Perfect clear"),
+            Self::TSpin(0) => write!(f, "T-spin"),
+            Self::TSpin(1) => write!(f, "T-spin single"),
+            Self::TSpin(2) => write!(f, "T-spin double"),
+            Self::TSpin(3) => write!(f, "T-spin triple"),
+            Self::TSpin(lines) => write!(f, "T-spin clearing {lines} lines"),
+        }
+    }
+}
+
+/// A fixed starting board, a fixed piece sequence and a goal, played in
+/// [`crate::GameMode::Puzzle`]. The game is lost if the sequence runs out before the goal is met.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Puzzle {
+    pub name: String,
+    /// Visible rows of the starting board in the map format: `x` is empty, lowercase letters
+    /// are blocks of the piece set, `#` is garbage and `=` a wall
+    pub board: Vec<String>,
+    /// Symbols of the pieces in the order they are dealt, e.g. `"TLI"`
+    pub sequence: String,
+    pub goal: PuzzleGoal,
+}
+
+impl Puzzle {
+    pub fn from_ron(text: &str) -> Result<Self, PuzzleError> {
+        ron::from_str(text).map_err(PuzzleError::Ron)
+    }
+
+    /// Reads a `.ron` file
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self, PuzzleError> {
+        let text = std::fs::read_to_string(path).map_err(PuzzleError::Io)?;
+        Self::from_ron(&text)
+    }
+
+    /// Width and visible height of the board
+    pub fn size(&self) -> (u16, u16) {
+        let width = self.board.first().map_or(0, |row| row.chars().count());
+        (width as u16, self.board.len() as u16)
+    }
+
+    /// The starting board with `hidden_rows` empty rows above it
+    pub fn map(&self, hidden_rows: u16) -> Result<Map, PuzzleError> {
+        let map = Map::parse(&self.board.join("\n")).map_err(PuzzleError::Map)?;
+        Ok(map.with_hidden_rows(hidden_rows))
+    }
+
+    /// The sequence as shapes of the piece set
+    pub fn shapes(&self, pieces: &PieceSet) -> Result<Vec<ShapeType>, PuzzleError> {
+        self.sequence
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|symbol| {
+                pieces.shape_type(symbol).ok_or_else(|| {
+                    PuzzleError::Invalid(format!("{}: {symbol} is not a piece", self.name))
+                })
+            })
+            .collect()
+    }
+
+    pub fn validate(&self, pieces: &PieceSet) -> Result<(), PuzzleError> {
+        let invalid = |reason: &str| Err(PuzzleError::Invalid(format!("{}: {reason}", self.name)));
+        let (width, _) = self.size();
+        if width == 0
+            || self
+                .board
+                .iter()
+                .any(|row| row.chars().count() != width as usize)
+        {
+            return invalid("all rows of the board need the same width");
+        }
+        let map = self.map(0)?;
+        for tile in map.inner.values() {
+            match tile {
+                Tile::CurrentTetromino(_) => {
+                    return invalid("the board must not contain an active piece")
+                }
+                Tile::Block(symbol) if pieces.shape_type(*symbol).is_none() => {
+                    return invalid(&format!("{symbol} is not a piece"));
+                }
+                _ => (),
+            }
+        }
+        if self.shapes(pieces)?.is_empty() {
+            return invalid("the sequence needs at least one piece");
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum PuzzleError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+    /// The board, lines and columns are counted from its first row
+    Map(MapParseError),
+    Invalid(String),
+}
+
+impl Display for PuzzleError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "cannot read puzzle: {e}"),
+            Self::Ron(e) => write!(f, "invalid RON puzzle: {e}"),
+            Self::Map(e) => write!(f, "invalid puzzle board: {e}"),
+            Self::Invalid(reason) => write!(f, "invalid puzzle {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for PuzzleError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_t_spin_double_asset() {
+        let puzzle =
+            Puzzle::from_ron(include_str!("../../../assets/puzzles/t_spin_double.ron")).unwrap();
+        let pieces = PieceSet::tetrominoes();
+        puzzle.validate(&pieces).unwrap();
+        assert_eq!(puzzle.goal, PuzzleGoal::TSpin(2));
+        assert_eq!(puzzle.shapes(&pieces).unwrap(), vec![ShapeType::T]);
+        let (width, height) = puzzle.size();
+        let map = puzzle.map(2).unwrap();
+        assert_eq!(
+            (map.width, map.height),
+            (width as usize, height as usize + 2)
+        );
+    }
+
+    #[test]
+    fn test_invalid_puzzles() {
+        let pieces = PieceSet::tetrominoes();
+        let puzzle = Puzzle {
+            name: "broken".to_string(),
+            board: vec!["xxxx".to_string(), "oxx".to_string()],
+            sequence: "T".to_string(),
+            goal: PuzzleGoal::Lines(1),
+        };
+        assert!(matches!(
+            puzzle.validate(&pieces),
+            Err(PuzzleError::Invalid(_))
+        ));
+        let puzzle = Puzzle {
+            board: vec!["xxxx".to_string(), "oxxq".to_string()],
+            ..puzzle
+        };
+        assert!(matches!(
+            puzzle.validate(&pieces),
+            Err(PuzzleError::Invalid(_))
+        ));
+        let puzzle = Puzzle {
+            board: vec!["xxxx".to_string(), "oxxo".to_string()],
+            sequence: "TQ".to_string(),
+            ..puzzle
+        };
+        assert!(matches!(
+            puzzle.validate(&pieces),
+            Err(PuzzleError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_goal_display() {
+        assert_eq!(PuzzleGoal::Lines(4).to_string(), "Clear 4 lines");
+        assert_eq!(PuzzleGoal::TSpin(2).to_string(), "T-spin double");
+    }
+}
@@ -0,0 +1,81 @@
+use std::time::Duration;
+
+use bevy::prelude::Resource;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    GameMode, GameModifier, LineClearGravity, PieceSet, Puzzle, Randomizer, SurvivalOptions,
+};
+
+/// Game rule options. Set in the [`crate::TetrisPlugin`], which inserts them as a resource
+#[derive(Debug, Clone, Serialize, Deserialize, Resource)]
+pub struct RuleOptions {
+    /// Goal and end of the game
+    pub mode: GameMode,
+    /// Board, pieces and goal of [`GameMode::Puzzle`]
+    pub puzzle: Option<Puzzle>,
+    /// Stacked on the game mode, applied in this order
+    pub modifiers: Vec<GameModifier>,
+    /// Pieces of the game, the standard tetrominoes by default
+    pub pieces: PieceSet,
+    /// Generator of the upcoming shapes
+    pub randomizer: Randomizer,
+    /// Number of upcoming shapes shown in the sidebar, from 0 to [`crate::MAX_PREVIEW`]
+    pub preview: usize,
+    /// Level of a new game
+    pub start_level: u32,
+    /// Fixed cells per frame the active piece falls. `None` follows the
+    /// [`crate::GRAVITY_TABLE`] of the current level
+    pub gravity: Option<f32>,
+    /// Time a piece rests on the stack before it locks
+    pub lock_delay: Duration,
+    /// What restarts the lock delay
+    pub lock_reset: LockReset,
+    /// Number of resets per piece with [`LockReset::Move`], afterwards it locks as soon as it
+    /// touches the stack
+    pub lock_reset_limit: u32,
+    /// How blocks above cleared lines fall
+    pub line_clear_gravity: LineClearGravity,
+    /// Rows above the visible board where pieces spawn, the map is this much higher than
+    /// [`crate::BoardOptions::map_size`]
+    pub hidden_rows: u16,
+    /// Blocks left in the hidden rows after a lock end the game, not only pieces locking
+    /// completely inside them
+    pub partial_lock_out: bool,
+    /// Garbage rows rise from the bottom, `None` for no garbage
+    pub survival: Option<SurvivalOptions>,
+}
+
+/// Lock delay reset behaviour
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LockReset {
+    /// Successful moves and rotations restart the lock delay, up to the reset limit
+    #[default]
+    Move,
+    /// Only falling below the lowest row reached so far restarts the lock delay
+    Step,
+    /// The lock delay runs for the whole piece
+    None,
+}
+
+impl Default for RuleOptions {
+    fn default() -> Self {
+        Self {
+            mode: GameMode::Marathon,
+            puzzle: None,
+            modifiers: vec![],
+            pieces: Default::default(),
+            randomizer: Default::default(),
+            preview: 3,
+            start_level: 1,
+            gravity: None,
+            lock_delay: Duration::from_millis(500),
+            lock_reset: LockReset::Move,
+            lock_reset_limit: 15,
+            line_clear_gravity: LineClearGravity::Naive,
+            hidden_rows: 20,
+            partial_lock_out: true,
+            survival: None,
+        }
+    }
+}
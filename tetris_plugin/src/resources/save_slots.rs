@@ -0,0 +1,116 @@
+use bevy::prelude::Resource;
+
+use crate::SaveSlot;
+
+/// All save slots of the store, the newest first
+#[derive(Debug, Clone, Default, PartialEq, Eq, Resource)]
+pub struct SaveSlots(pub Vec<SaveSlot>);
+
+impl SaveSlots {
+    pub fn get(&self, name: &str) -> Option<&SaveSlot> {
+        self.0.iter().find(|slot| slot.name == name)
+    }
+
+    pub fn newest(&self) -> Option<&SaveSlot> {
+        self.0.first()
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.0.iter().map(|slot| slot.name.clone()).collect()
+    }
+
+    /// Replaces the slot with the same name, the slots stay sorted by time
+    pub fn insert(&mut self, slot: SaveSlot) {
+        self.remove(&slot.name);
+        let index = self.0.partition_point(|s| s.timestamp >= slot.timestamp);
+        self.0.insert(index, slot);
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<SaveSlot> {
+        let index = self.0.iter().position(|slot| slot.name == name)?;
+        Some(self.0.remove(index))
+    }
+
+    /// `name` if no slot has it yet, otherwise the name with the first free number appended
+    pub fn unique_name(&self, name: &str) -> String {
+        (1..)
+            .map(|n| match n {
+                1 => name.to_string(),
+                n => format!("{name} ({n})"),
+            })
+            .find(|candidate| self.get(candidate).is_none())
+            .expect("there is always a free name")
+    }
+
+    /// True if the game of the slot is stored already, under its name or one which
+    /// [`SaveSlots::unique_name`] made from it
+    pub fn contains(&self, slot: &SaveSlot) -> bool {
+        let renamed = format!("{} (", slot.name);
+        self.0
+            .iter()
+            .any(|s| s.save == slot.save && (s.name == slot.name || s.name.starts_with(&renamed)))
+    }
+}
+
+/// Directory the pause menu exports slots to and imports them from
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, PartialEq, Eq, Resource)]
+pub struct SlotDirectory(pub std::path::PathBuf);
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Default for SlotDirectory {
+    fn default() -> Self {
+        Self("saves".into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GameMode;
+
+    fn slot(name: &str, timestamp: u64) -> SaveSlot {
+        SaveSlot {
+            name: name.to_string(),
+            timestamp,
+            mode: GameMode::Marathon,
+            score: 0,
+            level: 1,
+            thumbnail: String::new(),
+            save: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_slots_are_sorted_and_unique() {
+        let mut slots = SaveSlots::default();
+        slots.insert(slot("a", 10));
+        slots.insert(slot("b", 30));
+        slots.insert(slot("c", 20));
+        assert_eq!(slots.names(), ["b", "c", "a"]);
+        slots.insert(slot("a", 40));
+        assert_eq!(slots.names(), ["a", "b", "c"]);
+
+        assert_eq!(slots.unique_name("d"), "d");
+        slots.insert(slot("d (2)", 0));
+        slots.insert(slot("d", 0));
+        assert_eq!(slots.unique_name("d"), "d (3)");
+        assert_eq!(slots.remove("b").map(|s| s.timestamp), Some(30));
+        assert_eq!(slots.newest().unwrap().name, "a");
+    }
+
+    #[test]
+    fn test_contains_renamed_slots() {
+        let mut slots = SaveSlots::default();
+        let mut imported = slot("a", 10);
+        imported.save = "game".to_string();
+        assert!(!slots.contains(&imported));
+        slots.insert(slot("a", 20));
+        assert!(!slots.contains(&imported));
+        slots.insert(SaveSlot {
+            name: slots.unique_name(&imported.name),
+            ..imported.clone()
+        });
+        assert!(slots.contains(&imported));
+    }
+}
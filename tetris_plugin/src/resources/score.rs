@@ -1,20 +1,35 @@
-use std::ops::AddAssign;
-
-use bevy::prelude::Resource;
-
-use crate::ScoreEvent;
-
-#[derive(Resource)]
-pub struct Score(pub u64);
-
-impl AddAssign<ScoreEvent> for Score {
-    fn add_assign(&mut self, rhs: ScoreEvent) {
-        self.0 += rhs.0;
-    }
-}
-
-impl AddAssign for Score {
-    fn add_assign(&mut self, rhs: Self) {
-        self.0 += rhs.0;
-    }
-}
+use std::ops::AddAssign;
+
+use bevy::prelude::Resource;
+use serde::{Deserialize, Serialize};
+
+/// Points of the current game, split by where they came from
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize, Resource)]
+pub struct Score {
+    /// Singles, doubles, triples, tetrises and T-spins
+    pub line_clears: u64,
+    /// Consecutive clearing locks
+    pub combos: u64,
+    /// Difficult clears in a row
+    pub back_to_back: u64,
+    /// Soft and hard drops
+    pub drops: u64,
+    /// Clears which leave an empty board
+    pub perfect_clears: u64,
+}
+
+impl Score {
+    pub fn total(&self) -> u64 {
+        self.line_clears + self.combos + self.back_to_back + self.drops + self.perfect_clears
+    }
+}
+
+impl AddAssign for Score {
+    fn add_assign(&mut self, rhs: Self) {
+        self.line_clears += rhs.line_clears;
+        self.combos += rhs.combos;
+        self.back_to_back += rhs.back_to_back;
+        self.drops += rhs.drops;
+        self.perfect_clears += rhs.perfect_clears;
+    }
+}
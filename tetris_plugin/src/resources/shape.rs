@@ -4,8 +4,8 @@ use std::{
     fmt::{Display, Formatter},
 };
 
-use bevy::prelude::{trace, warn, Color, Component, Reflect, Resource};
-use rand::{distributions::Standard, prelude::Distribution, Rng};
+use bevy::prelude::{trace, warn, Component, Reflect};
+use serde::{Deserialize, Serialize};
 
 use crate::{Coordinates, Matrix, MoveEvent, TileBlueprint};
 
@@ -53,6 +53,8 @@ impl PartialEq<(i16, i16)> for ShapePosition {
 #[derive(Debug, Clone)]
 pub struct Shape {
     pub shape_type: ShapeType,
+    /// Save file character of the piece
+    pub symbol: char,
     pub anker: Coordinates, // Should be the top left corner
     pub layout: Matrix,
     pub positions: BTreeMap<ShapePosition, TileBlueprint>, //[TileBlueprint; N], // size = layout.x * layout.y
@@ -144,244 +146,59 @@ use bevy::prelude::World;
 //     // }
 // }
 
+/// Index of a piece in the [`crate::PieceSet`] of the rules. The constants name the pieces of
+/// the standard tetromino set.
 #[cfg_attr(feature = "debug", derive(Reflect))]
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
-pub enum ShapeType {
-    I = 0,
-    O = 1,
-    T = 2,
-    S = 3,
-    Z = 4,
-    L = 5,
-    J = 6,
-}
-#[derive(Debug, Clone, PartialEq, Eq, Resource)]
-pub struct NextShape(pub ShapeType);
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct ShapeType(pub u8);
 
 impl Display for ShapeType {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "({})", self)
+        write!(f, "({})", self.0)
     }
 }
 
 impl ShapeType {
-    pub fn get_color(&self) -> Color {
-        match self {
-            &Self::I => Color::srgb(0.0, 0.7, 0.7),
-            &Self::O => Color::srgb(0.7, 0.7, 0.0), // square, yellow
-            &Self::T => Color::srgb(0.7, 0.0, 0.7), // T, purple
-            &Self::Z => Color::srgb(0.7, 0.0, 0.0), // Z, red
-            &Self::S => Color::srgb(0.0, 0.7, 0.0), // S, green
-            &Self::L => Color::srgb(0.0, 0.0, 0.7), // L, blue
-            &Self::J => Color::srgb(0.9, 0.25, 0.0), // J, orange
-        }
-    }
-
-    pub(crate) fn as_char(&self) -> char {
-        match self {
-            &Self::I => 'i',
-            &Self::O => 'o',
-            &Self::T => 't',
-            &Self::Z => 'z',
-            &Self::S => 's',
-            &Self::L => 'l',
-            &Self::J => 'j',
-        }
-    }
-    pub(crate) fn from_char(c: char) -> Self {
-        match c {
-            'i' => Self::I,
-            'o' => Self::O,
-            't' => Self::T,
-            'z' => Self::Z,
-            's' => Self::S,
-            'l' => Self::L,
-            'j' => Self::J,
-            _ => panic!("unkown char"),
-        }
-    }
-
-    pub(crate) fn is_shape_type(c: char) -> bool {
-        match c {
-            'i' => true,
-            'o' => true,
-            't' => true,
-            'z' => true,
-            's' => true,
-            'l' => true,
-            'j' => true,
-            _ => false,
-        }
-    }
+    pub const I: ShapeType = ShapeType(0);
+    pub const O: ShapeType = ShapeType(1);
+    pub const T: ShapeType = ShapeType(2);
+    pub const S: ShapeType = ShapeType(3);
+    pub const Z: ShapeType = ShapeType(4);
+    pub const L: ShapeType = ShapeType(5);
+    pub const J: ShapeType = ShapeType(6);
 }
 
 impl Shape {
-    pub fn blueprint(shape_type: ShapeType) -> Shape {
-        Shape::blueprints().remove(shape_type as usize)
-    }
-
     pub fn initial_occupied(&self) -> Vec<&ShapePosition> {
         self.positions.keys().collect()
     }
 
-    fn blueprints() -> Vec<Shape> {
-        let blueprint = vec![
-            // line, cyan
-            Shape {
-                shape_type: ShapeType::I,
-                anker: (0, 0).into(), // Should be the top left corner
-                layout: Matrix {
-                    width: 1,
-                    height: 4,
-                },
-                positions: vec![
-                    ((0, 3).into(), TileBlueprint::CurrentTetromino),
-                    ((0, 2).into(), TileBlueprint::CurrentTetromino),
-                    ((0, 1).into(), TileBlueprint::CurrentTetromino),
-                    ((0, 0).into(), TileBlueprint::CurrentTetromino),
-                ]
-                .into_iter()
-                .collect::<BTreeMap<ShapePosition, TileBlueprint>>(),
+    /// Builds a shape in its spawn orientation from the rows of its square rotation box.
+    /// `#` marks a block, every other char an empty tile.
+    pub(crate) fn from_rows(shape_type: ShapeType, symbol: char, rows: &[&str]) -> Shape {
+        let positions = rows
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.chars().enumerate().map(move |(x, c)| {
+                    let blueprint = if c == '#' {
+                        TileBlueprint::CurrentTetromino
+                    } else {
+                        TileBlueprint::Empty
+                    };
+                    ((x as i16, y as i16).into(), blueprint)
+                })
+            })
+            .collect::<BTreeMap<ShapePosition, TileBlueprint>>();
+        Shape {
+            shape_type,
+            symbol,
+            anker: (0, 0).into(), // Should be the top left corner
+            layout: Matrix {
+                width: rows.len() as u16,
+                height: rows.len() as u16,
             },
-            // square, yellow
-            Shape {
-                shape_type: ShapeType::O,
-                anker: (0, 0).into(), // Should be the top left corner
-                layout: Matrix {
-                    width: 2,
-                    height: 2,
-                },
-                positions: vec![
-                    ((0, 0).into(), TileBlueprint::CurrentTetromino),
-                    ((0, 1).into(), TileBlueprint::CurrentTetromino),
-                    ((1, 0).into(), TileBlueprint::CurrentTetromino),
-                    ((1, 1).into(), TileBlueprint::CurrentTetromino),
-                ]
-                .into_iter()
-                .collect::<BTreeMap<ShapePosition, TileBlueprint>>(),
-            },
-            Shape {
-                shape_type: ShapeType::T,
-                anker: (0, 0).into(), // Should be the top left corner
-                layout: Matrix {
-                    width: 3,
-                    height: 2,
-                },
-                positions: vec![
-                    ((0, 0).into(), TileBlueprint::CurrentTetromino),
-                    ((0, 1).into(), TileBlueprint::CurrentTetromino),
-                    ((0, 2).into(), TileBlueprint::CurrentTetromino),
-                    ((1, 1).into(), TileBlueprint::CurrentTetromino),
-                    ((1, 0).into(), TileBlueprint::Empty),
-                    ((1, 2).into(), TileBlueprint::Empty),
-                ]
-                .into_iter()
-                .collect::<BTreeMap<ShapePosition, TileBlueprint>>(),
-            },
-            Shape {
-                shape_type: ShapeType::S,
-                anker: (0, 0).into(), // Should be the top left corner
-                layout: Matrix {
-                    width: 3,
-                    height: 2,
-                },
-                positions: vec![
-                    ((0, 1).into(), TileBlueprint::CurrentTetromino),
-                    ((1, 1).into(), TileBlueprint::CurrentTetromino),
-                    ((1, 0).into(), TileBlueprint::CurrentTetromino),
-                    ((2, 0).into(), TileBlueprint::CurrentTetromino),
-                    ((0, 0).into(), TileBlueprint::Empty),
-                    ((2, 1).into(), TileBlueprint::Empty),
-                ]
-                .into_iter()
-                .collect::<BTreeMap<ShapePosition, TileBlueprint>>(),
-            },
-            Shape {
-                shape_type: ShapeType::Z,
-                anker: (0, 0).into(), // Should be the top left corner
-                layout: Matrix {
-                    width: 3,
-                    height: 2,
-                },
-                positions: vec![
-                    ((0, 0).into(), TileBlueprint::CurrentTetromino),
-                    ((1, 0).into(), TileBlueprint::CurrentTetromino),
-                    ((1, 1).into(), TileBlueprint::CurrentTetromino),
-                    ((2, 1).into(), TileBlueprint::CurrentTetromino),
-                    ((2, 0).into(), TileBlueprint::Empty),
-                    ((0, 1).into(), TileBlueprint::Empty),
-                ]
-                .into_iter()
-                .collect::<BTreeMap<ShapePosition, TileBlueprint>>(),
-            },
-            Shape {
-                shape_type: ShapeType::L,
-                anker: (0, 0).into(), // Should be the top left corner
-                layout: Matrix {
-                    width: 3,
-                    height: 2,
-                },
-                positions: vec![
-                    ((0, 0).into(), TileBlueprint::CurrentTetromino),
-                    ((0, 1).into(), TileBlueprint::CurrentTetromino),
-                    ((1, 0).into(), TileBlueprint::CurrentTetromino),
-                    ((2, 0).into(), TileBlueprint::CurrentTetromino),
-                    ((1, 1).into(), TileBlueprint::Empty),
-                    ((2, 1).into(), TileBlueprint::Empty),
-                ]
-                .into_iter()
-                .collect::<BTreeMap<ShapePosition, TileBlueprint>>(),
-            },
-            Shape {
-                shape_type: ShapeType::J,
-                anker: (0, 0).into(), // Should be the top left corner
-                layout: Matrix {
-                    width: 3,
-                    height: 2,
-                },
-                positions: vec![
-                    ((0, 0).into(), TileBlueprint::CurrentTetromino),
-                    ((1, 0).into(), TileBlueprint::CurrentTetromino),
-                    ((2, 0).into(), TileBlueprint::CurrentTetromino),
-                    ((2, 1).into(), TileBlueprint::CurrentTetromino),
-                    ((1, 1).into(), TileBlueprint::Empty),
-                    ((0, 1).into(), TileBlueprint::Empty),
-                ]
-                .into_iter()
-                .collect::<BTreeMap<ShapePosition, TileBlueprint>>(),
-            },
-        ];
-
-        // for b in blueprint.iter() {
-        //     let size = b.layout.width * b.layout.height;
-        //     assert_eq!(size as usize, b.positions.len());
-        //     // for i in b.anker.x..b.layout.width - b.anker.x {
-        //         // for j in b.anker.y..b.layout.height - b.anker.y {
-        //     for i in 0..b.layout.width {
-        //         for j in 0..b.layout.height {
-        //             let x = b.positions.get(&ShapePosition {
-        //                 x: i as i16,
-        //                 y: j as i16,
-        //             });
-        //             assert!(x.is_some(), "{b}: ({i} {j})");
-        //         }
-        //     }
-        // }
-
-        blueprint
-    }
-}
-
-impl Distribution<ShapeType> for Standard {
-    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> ShapeType {
-        match rng.gen_range(0..6) {
-            0 => ShapeType::I,
-            1 => ShapeType::O,
-            2 => ShapeType::T,
-            3 => ShapeType::S,
-            4 => ShapeType::Z,
-            5 => ShapeType::L,
-            _ => ShapeType::J,
+            positions,
         }
     }
 }
@@ -389,7 +206,7 @@ impl Distribution<ShapeType> for Standard {
 #[cfg(test)]
 mod tests {
     use super::ShapePosition;
-    use crate::Shape;
+    use crate::{PieceSet, ShapeType};
 
     #[test]
     fn test_from_shapepos_from_i16() {
@@ -399,7 +216,13 @@ mod tests {
     }
 
     #[test]
-    fn test_blueprints() {
-        Shape::blueprints();
+    fn test_shapes() {
+        let set = PieceSet::tetrominoes();
+        for shape_type in set.shape_types() {
+            let shape = set.shape(shape_type);
+            let size = shape.layout.width * shape.layout.height;
+            assert_eq!(size as usize, shape.positions.len(), "{shape}");
+        }
+        assert_eq!(set.shape(ShapeType::I).layout.width, 4);
     }
 }
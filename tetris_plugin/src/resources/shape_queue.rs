@@ -1 +1,411 @@
-
+use std::{collections::VecDeque, fmt::Debug};
+
+use bevy::prelude::Resource;
+use rand::{seq::SliceRandom, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{PieceSet, ShapeType};
+
+/// Random number generator of the engine and the piece generators. It is the algorithm of
+/// `StdRng`, but can be saved, so a loaded game deals the same shapes.
+pub type GameRng = ChaCha12Rng;
+
+/// Saves a [`GameRng`] as its seed, stream and word position, RON has no 128 bit integers
+pub(crate) mod saved_rng {
+    use rand::SeedableRng;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::GameRng;
+
+    pub fn serialize<S: Serializer>(rng: &GameRng, serializer: S) -> Result<S::Ok, S::Error> {
+        // 2^64 words are never drawn in a game
+        (rng.get_seed(), rng.get_stream(), rng.get_word_pos() as u64).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<GameRng, D::Error> {
+        let (seed, stream, word_pos) = <([u8; 32], u64, u64)>::deserialize(deserializer)?;
+        let mut rng = GameRng::from_seed(seed);
+        rng.set_stream(stream);
+        rng.set_word_pos(word_pos as u128);
+        Ok(rng)
+    }
+}
+
+/// Decides which shape comes next
+pub trait PieceGenerator: Debug + Send + Sync {
+    fn next_shape(&mut self) -> ShapeType;
+
+    /// Needed to clone the [`crate::GameEngine`], which owns a boxed generator
+    fn box_clone(&self) -> Box<dyn PieceGenerator>;
+
+    /// State for a [`crate::SaveGame`]. Generators without one are replaced by a new generator
+    /// of the rules when the game is loaded.
+    fn state(&self) -> Option<GeneratorState> {
+        None
+    }
+}
+
+/// Saved state of the built-in generators
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum GeneratorState {
+    SevenBag(SevenBag),
+    Sequence(Sequence),
+    Uniform(Uniform),
+    NesReroll(NesReroll),
+    TgmHistory(TgmHistory),
+}
+
+impl GeneratorState {
+    pub fn generator(self) -> Box<dyn PieceGenerator> {
+        match self {
+            Self::SevenBag(generator) => Box::new(generator),
+            Self::Sequence(generator) => Box::new(generator),
+            Self::Uniform(generator) => Box::new(generator),
+            Self::NesReroll(generator) => Box::new(generator),
+            Self::TgmHistory(generator) => Box::new(generator),
+        }
+    }
+}
+
+impl Clone for Box<dyn PieceGenerator> {
+    fn clone(&self) -> Self {
+        self.box_clone()
+    }
+}
+
+/// Available piece generators, selected in the [`crate::RuleOptions`]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Randomizer {
+    /// Guideline randomizer, deals all seven shapes in random order before the next bag starts
+    #[default]
+    SevenBag,
+    /// Every shape has the same chance, independent of the previous ones
+    Uniform,
+    /// Rolls once more if the shape is the same as the previous one
+    NesReroll,
+    /// Avoids the last four shapes, up to four rolls
+    TgmHistory,
+}
+
+impl Randomizer {
+    pub fn generator(&self, pieces: &PieceSet) -> Box<dyn PieceGenerator> {
+        self.seeded_generator(pieces, GameRng::from_entropy())
+    }
+
+    /// A generator dealing the shapes of the piece set
+    pub fn seeded_generator(&self, pieces: &PieceSet, rng: GameRng) -> Box<dyn PieceGenerator> {
+        let shapes = pieces.shape_types();
+        match self {
+            Self::SevenBag => Box::new(SevenBag {
+                rng,
+                shapes,
+                bag: vec![],
+            }),
+            Self::Uniform => Box::new(Uniform { rng, shapes }),
+            Self::NesReroll => Box::new(NesReroll {
+                rng,
+                shapes,
+                last: None,
+            }),
+            Self::TgmHistory => {
+                // the first shape is never S, Z or O
+                let first: Vec<ShapeType> = shapes
+                    .iter()
+                    .copied()
+                    .filter(|s| !matches!(pieces.symbol(*s), 's' | 'z' | 'o'))
+                    .collect();
+                let start = pieces.shape_type('z').unwrap_or(shapes[0]);
+                Box::new(TgmHistory {
+                    rng,
+                    history: VecDeque::from([start; 4]),
+                    first: if first.is_empty() {
+                        shapes.clone()
+                    } else {
+                        first
+                    },
+                    shapes,
+                    started: false,
+                })
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SevenBag {
+    #[serde(with = "saved_rng")]
+    rng: GameRng,
+    shapes: Vec<ShapeType>,
+    bag: Vec<ShapeType>,
+}
+
+impl PieceGenerator for SevenBag {
+    fn next_shape(&mut self) -> ShapeType {
+        if self.bag.is_empty() {
+            self.bag = self.shapes.clone();
+            self.bag.shuffle(&mut self.rng);
+        }
+        self.bag.pop().expect("bag was refilled")
+    }
+
+    fn box_clone(&self) -> Box<dyn PieceGenerator> {
+        Box::new(self.clone())
+    }
+
+    fn state(&self) -> Option<GeneratorState> {
+        Some(GeneratorState::SevenBag(self.clone()))
+    }
+}
+
+/// Deals the shapes in the given order and starts over at the end. Puzzles stop dealing when
+/// the sequence ran out.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Sequence {
+    shapes: Vec<ShapeType>,
+    next: usize,
+}
+
+impl Sequence {
+    pub fn new(shapes: Vec<ShapeType>) -> Self {
+        assert!(!shapes.is_empty(), "a sequence needs at least one shape");
+        Self { shapes, next: 0 }
+    }
+}
+
+impl PieceGenerator for Sequence {
+    fn next_shape(&mut self) -> ShapeType {
+        let shape = self.shapes[self.next];
+        self.next = (self.next + 1) % self.shapes.len();
+        shape
+    }
+
+    fn box_clone(&self) -> Box<dyn PieceGenerator> {
+        Box::new(self.clone())
+    }
+
+    fn state(&self) -> Option<GeneratorState> {
+        Some(GeneratorState::Sequence(self.clone()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Uniform {
+    #[serde(with = "saved_rng")]
+    rng: GameRng,
+    shapes: Vec<ShapeType>,
+}
+
+impl PieceGenerator for Uniform {
+    fn next_shape(&mut self) -> ShapeType {
+        *self.shapes.choose(&mut self.rng).expect("not empty")
+    }
+
+    fn box_clone(&self) -> Box<dyn PieceGenerator> {
+        Box::new(self.clone())
+    }
+
+    fn state(&self) -> Option<GeneratorState> {
+        Some(GeneratorState::Uniform(self.clone()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NesReroll {
+    #[serde(with = "saved_rng")]
+    rng: GameRng,
+    shapes: Vec<ShapeType>,
+    last: Option<ShapeType>,
+}
+
+impl NesReroll {
+    fn roll(&mut self) -> ShapeType {
+        *self.shapes.choose(&mut self.rng).expect("not empty")
+    }
+}
+
+impl PieceGenerator for NesReroll {
+    fn next_shape(&mut self) -> ShapeType {
+        let mut shape = self.roll();
+        if Some(shape) == self.last {
+            shape = self.roll();
+        }
+        self.last = Some(shape);
+        shape
+    }
+
+    fn box_clone(&self) -> Box<dyn PieceGenerator> {
+        Box::new(self.clone())
+    }
+
+    fn state(&self) -> Option<GeneratorState> {
+        Some(GeneratorState::NesReroll(self.clone()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TgmHistory {
+    #[serde(with = "saved_rng")]
+    rng: GameRng,
+    shapes: Vec<ShapeType>,
+    history: VecDeque<ShapeType>,
+    /// Candidates for the first shape
+    first: Vec<ShapeType>,
+    started: bool,
+}
+
+impl PieceGenerator for TgmHistory {
+    fn next_shape(&mut self) -> ShapeType {
+        let shape = if !self.started {
+            self.started = true;
+            *self.first.choose(&mut self.rng).expect("not empty")
+        } else {
+            let mut shape = *self.shapes.choose(&mut self.rng).expect("not empty");
+            for _ in 1..4 {
+                if !self.history.contains(&shape) {
+                    break;
+                }
+                shape = *self.shapes.choose(&mut self.rng).expect("not empty");
+            }
+            shape
+        };
+        self.history.pop_front();
+        self.history.push_back(shape);
+        shape
+    }
+
+    fn box_clone(&self) -> Box<dyn PieceGenerator> {
+        Box::new(self.clone())
+    }
+
+    fn state(&self) -> Option<GeneratorState> {
+        Some(GeneratorState::TgmHistory(self.clone()))
+    }
+}
+
+/// Maximum number of upcoming shapes which can be shown to the player
+pub const MAX_PREVIEW: usize = 6;
+
+/// The upcoming shapes shown in the sidebar, the first one spawns next
+#[derive(Debug, Clone, Default, PartialEq, Eq, Resource)]
+pub struct PieceQueue(pub Vec<ShapeType>);
+
+/// Upcoming shapes. The queue is refilled from the generator, so it always holds
+/// [`MAX_PREVIEW`] shapes.
+#[derive(Debug, Clone)]
+pub struct ShapeQueue {
+    generator: Box<dyn PieceGenerator>,
+    queue: VecDeque<ShapeType>,
+}
+
+impl ShapeQueue {
+    pub fn new(generator: Box<dyn PieceGenerator>) -> Self {
+        Self::with_shapes(generator, vec![])
+    }
+
+    /// A queue which deals the given shapes before the ones of the generator, e.g. of a loaded
+    /// game
+    pub fn with_shapes(generator: Box<dyn PieceGenerator>, shapes: Vec<ShapeType>) -> Self {
+        let mut queue = Self {
+            generator,
+            queue: shapes.into(),
+        };
+        queue.fill();
+        queue
+    }
+
+    /// All shapes in the queue, the first one spawns next
+    pub fn shapes(&self) -> Vec<ShapeType> {
+        self.queue.iter().copied().collect()
+    }
+
+    pub fn generator_state(&self) -> Option<GeneratorState> {
+        self.generator.state()
+    }
+
+    pub fn peek(&self) -> ShapeType {
+        *self.queue.front().expect("queue is never empty")
+    }
+
+    pub fn pop(&mut self) -> ShapeType {
+        let shape = self.queue.pop_front().expect("queue is never empty");
+        self.fill();
+        shape
+    }
+
+    /// The next `count` shapes, at most [`MAX_PREVIEW`]
+    pub fn preview(&self, count: usize) -> Vec<ShapeType> {
+        self.queue
+            .iter()
+            .take(count.min(MAX_PREVIEW))
+            .copied()
+            .collect()
+    }
+
+    fn fill(&mut self) {
+        while self.queue.len() < MAX_PREVIEW {
+            self.queue.push_back(self.generator.next_shape());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shapes(randomizer: Randomizer, count: usize) -> Vec<ShapeType> {
+        let mut generator =
+            randomizer.seeded_generator(&PieceSet::tetrominoes(), GameRng::seed_from_u64(7));
+        (0..count).map(|_| generator.next_shape()).collect()
+    }
+
+    #[test]
+    fn test_seven_bag_deals_every_shape_per_bag() {
+        for bag in shapes(Randomizer::SevenBag, 70).chunks(7) {
+            for shape in PieceSet::tetrominoes().shape_types() {
+                assert!(bag.contains(&shape), "{shape:?} missing in {bag:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_uniform_produces_every_shape() {
+        let shapes = shapes(Randomizer::Uniform, 1000);
+        for shape in PieceSet::tetrominoes().shape_types() {
+            assert!(shapes.contains(&shape), "{shape:?} never produced");
+        }
+    }
+
+    #[test]
+    fn test_nes_reroll_rarely_repeats() {
+        let shapes = shapes(Randomizer::NesReroll, 7000);
+        let repeats = shapes.windows(2).filter(|w| w[0] == w[1]).count();
+        // 1/7 * 1/7 with a single reroll, compared to 1/7 without
+        assert!(repeats < 300, "{repeats} repeats");
+    }
+
+    #[test]
+    fn test_tgm_history_first_shape() {
+        for seed in 0..50 {
+            let mut generator = Randomizer::TgmHistory
+                .seeded_generator(&PieceSet::tetrominoes(), GameRng::seed_from_u64(seed));
+            let first = generator.next_shape();
+            assert!(!matches!(first, ShapeType::S | ShapeType::Z | ShapeType::O));
+        }
+    }
+
+    #[test]
+    fn test_queue_peek_is_next_pop() {
+        let mut queue = ShapeQueue::new(Randomizer::Uniform.generator(&PieceSet::tetrominoes()));
+        for _ in 0..20 {
+            let next = queue.peek();
+            let preview = queue.preview(MAX_PREVIEW);
+            assert_eq!(preview.len(), MAX_PREVIEW);
+            assert_eq!(preview[0], next);
+            assert_eq!(queue.pop(), next);
+            assert_eq!(queue.preview(MAX_PREVIEW)[..MAX_PREVIEW - 1], preview[1..]);
+        }
+        assert_eq!(queue.preview(0), vec![]);
+        assert_eq!(queue.preview(10).len(), MAX_PREVIEW);
+    }
+}
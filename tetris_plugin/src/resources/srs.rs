@@ -0,0 +1,118 @@
+use serde::{Deserialize, Serialize};
+
+use crate::RotateEvent;
+
+/// Orientation of a piece according to the Super Rotation System
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+pub enum RotationState {
+    /// Spawn orientation, called 0
+    #[default]
+    Spawn,
+    /// One clockwise rotation from spawn, called R
+    Right,
+    /// Two rotations from spawn, called 2
+    Reverse,
+    /// One counter clockwise rotation from spawn, called L
+    Left,
+}
+
+impl RotationState {
+    pub const ALL: [RotationState; 4] = [Self::Spawn, Self::Right, Self::Reverse, Self::Left];
+
+    pub fn rotate(self, direction: &RotateEvent) -> Self {
+        let steps = match direction {
+            RotateEvent::ClockWise => 1,
+            RotateEvent::CounterClockWise => 3,
+        };
+        Self::ALL[(self.index() + steps) % 4]
+    }
+
+    /// Number of clockwise rotations from the spawn orientation
+    pub fn index(self) -> usize {
+        match self {
+            Self::Spawn => 0,
+            Self::Right => 1,
+            Self::Reverse => 2,
+            Self::Left => 3,
+        }
+    }
+}
+
+// Offsets from the guideline with y pointing up, in the order they are tested.
+// Index is the rotation state the piece rotates from, clockwise table.
+const JLSTZ_KICKS_CW: [[(i16, i16); 5]; 4] = [
+    [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)], // 0 -> R
+    [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],     // R -> 2
+    [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],    // 2 -> L
+    [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],  // L -> 0
+];
+const JLSTZ_KICKS_CCW: [[(i16, i16); 5]; 4] = [
+    [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],    // 0 -> L
+    [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],     // R -> 0
+    [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)], // 2 -> R
+    [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],  // L -> 2
+];
+const I_KICKS_CW: [[(i16, i16); 5]; 4] = [
+    [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)], // 0 -> R
+    [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)], // R -> 2
+    [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)], // 2 -> L
+    [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)], // L -> 0
+];
+const I_KICKS_CCW: [[(i16, i16); 5]; 4] = [
+    [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)], // 0 -> L
+    [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)], // R -> 0
+    [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)], // 2 -> R
+    [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)], // L -> 2
+];
+
+/// Wall kick tables of a piece in a [`crate::PieceSet`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Kicks {
+    /// Only rotates in place, like the O piece
+    None,
+    /// SRS table of the J, L, S, T and Z pieces
+    Srs,
+    /// SRS table of the I piece
+    SrsI,
+    /// Offsets with y pointing up like the guideline tables, in the order they are tested.
+    /// Index is the rotation state the piece rotates from.
+    Custom {
+        clockwise: [Vec<(i16, i16)>; 4],
+        counter_clockwise: [Vec<(i16, i16)>; 4],
+    },
+}
+
+impl Kicks {
+    /// Wall kick offsets for a rotation, converted to board coordinates where y points down.
+    pub fn wall_kicks(&self, from: RotationState, direction: &RotateEvent) -> Vec<(i16, i16)> {
+        let offsets: &[(i16, i16)] = match (self, direction) {
+            (Self::None, _) => return vec![(0, 0)],
+            (Self::SrsI, RotateEvent::ClockWise) => &I_KICKS_CW[from.index()],
+            (Self::SrsI, RotateEvent::CounterClockWise) => &I_KICKS_CCW[from.index()],
+            (Self::Srs, RotateEvent::ClockWise) => &JLSTZ_KICKS_CW[from.index()],
+            (Self::Srs, RotateEvent::CounterClockWise) => &JLSTZ_KICKS_CCW[from.index()],
+            (Self::Custom { clockwise, .. }, RotateEvent::ClockWise) => &clockwise[from.index()],
+            (
+                Self::Custom {
+                    counter_clockwise, ..
+                },
+                RotateEvent::CounterClockWise,
+            ) => &counter_clockwise[from.index()],
+        };
+        offsets.iter().map(|(x, y)| (*x, -*y)).collect()
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        match self {
+            Self::Custom {
+                clockwise,
+                counter_clockwise,
+            } if clockwise.iter().chain(counter_clockwise).any(Vec::is_empty) => {
+                Err("every rotation needs at least one kick offset".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+}
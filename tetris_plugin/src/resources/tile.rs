@@ -37,10 +37,27 @@ pub enum Tile {
     CurrentTetromino(char),
     // not movable
     Block(char),
+    /// Not movable, pushed up from the bottom in survival games
+    Garbage,
+    /// Solid cell of the board layout. It is never cleared, a line is full when all other
+    /// cells are blocks.
+    Wall,
     /// Empty tile
     Empty,
 }
 
+/// Character of [`Tile::Garbage`] in save files
+pub const GARBAGE_SYMBOL: char = '#';
+
+/// Colour of [`Tile::Garbage`] blocks
+pub const GARBAGE_COLOR: bevy::prelude::Color = bevy::prelude::Color::srgb(0.5, 0.5, 0.5);
+
+/// Character of [`Tile::Wall`] in save files
+pub const WALL_SYMBOL: char = '=';
+
+/// Colour of [`Tile::Wall`] cells
+pub const WALL_COLOR: bevy::prelude::Color = bevy::prelude::Color::srgb(0.25, 0.25, 0.3);
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, PartialOrd, Ord)]
 pub enum TileBlueprint {
     CurrentTetromino,
@@ -55,6 +72,8 @@ impl Display for Tile {
                 // Tile::Block(Color(r, g, b), e) => format!("[{:?}]", e).truecolor(*r, *g, *b),
                 // Tile::CurrentTetromino(Color(r, g, b), e) =>
                 Tile::Block(c) => format!("{c}").red(),
+                Tile::Garbage => GARBAGE_SYMBOL.to_string().white(),
+                Tile::Wall => WALL_SYMBOL.to_string().blue(),
                 Tile::CurrentTetromino(c) => format!("{c}").to_ascii_uppercase().green(),
                 Tile::Empty => " ".normal(),
             }
@@ -63,10 +82,34 @@ impl Display for Tile {
 }
 
 impl Tile {
-    /// Is the tile a block?
+    /// Reads a cell of the map text format: `x` is empty, lowercase letters are blocks,
+    /// uppercase letters the current tetromino, `#` garbage and `=` a wall
+    pub fn from_symbol(c: char) -> Option<Self> {
+        match c {
+            'x' => Some(Self::Empty),
+            GARBAGE_SYMBOL => Some(Self::Garbage),
+            WALL_SYMBOL => Some(Self::Wall),
+            c if c.is_ascii_lowercase() => Some(Self::Block(c)),
+            c if c.is_ascii_uppercase() => Some(Self::CurrentTetromino(c.to_ascii_lowercase())),
+            _ => None,
+        }
+    }
+
+    /// Character of the tile in the map text format
+    pub fn symbol(&self) -> char {
+        match self {
+            Self::Block(c) => *c,
+            Self::Garbage => GARBAGE_SYMBOL,
+            Self::Wall => WALL_SYMBOL,
+            Self::CurrentTetromino(c) => c.to_ascii_uppercase(),
+            Self::Empty => 'x',
+        }
+    }
+
+    /// Is the tile a block? Garbage counts as a block
     pub const fn is_block(&self) -> bool {
         // matches!(self, Self::Block(_, _))
-        matches!(self, Self::Block(_))
+        matches!(self, Self::Block(_) | Self::Garbage)
     }
     pub const fn is_moveable(&self) -> bool {
         // matches!(self, Self::CurrentTetromino(_, _))
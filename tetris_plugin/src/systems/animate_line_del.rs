@@ -3,7 +3,7 @@ use std::{collections::BTreeMap, time::Duration};
 use bevy::prelude::*;
 use bevy_tweening::{lens::TransformPositionLens, Animator, Tween};
 
-use crate::{Board, Coordinates, Transitions};
+use crate::{Block, Board, Coordinates, Transitions};
 
 pub(crate) fn animate(
     mut commands: Commands,
@@ -11,7 +11,7 @@ pub(crate) fn animate(
     // mut map: ResMut<Map>,
     mut transitions: Option<ResMut<Transitions>>,
     // new_on_the_block: Query<(Entity, &Coordinates), Added<Block>>,
-    all: Query<(Entity, &Coordinates, &mut Transform)>,
+    all: Query<(Entity, &Coordinates, &mut Transform), With<Block>>,
 ) {
     let c_e_map: BTreeMap<&Coordinates, (&Transform, Entity)> =
         all.iter().map(|(e, c, t)| (c, (t, e))).collect();
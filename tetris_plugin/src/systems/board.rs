@@ -3,9 +3,11 @@ use bevy::window::{PrimaryWindow, Window};
 use bevy::{log, math::Vec3Swizzles, prelude::*};
 
 use crate::{
-    bounds::Bounds2, Board, BoardAssets, BoardOptions, BoardPosition, Map, SpawnEvent, TileSize,
+    bounds::Bounds2, ActiveModifiers, BaseColor, Block, Board, BoardAssets, BoardOptions,
+    BoardPosition, GameEngine, GameMode, Map, PieceSet, RuleOptions, ShapePosition, SpawnEvent,
+    Tetromino, Tile, TileSize, GARBAGE_COLOR, WALL_COLOR,
 };
-use crate::{Score, TileMapRoot};
+use crate::{Score, TileMapRoot, Wall};
 
 pub fn update_board_position(board: ResMut<Board>, window: Query<&Window, With<PrimaryWindow>>) {
     let windows = window.single().size();
@@ -16,17 +18,152 @@ pub fn update_board_position(board: ResMut<Board>, window: Query<&Window, With<P
 pub fn create_board(
     mut commands: Commands,
     options: Res<BoardOptions>,
+    rules: Res<RuleOptions>,
     window: Query<&Window, With<PrimaryWindow>>,
     board_assets: Res<BoardAssets>,
     mut spawn_ewr: EventWriter<SpawnEvent>,
 ) {
     println!("windows {:?}", window);
+    let modifiers = ActiveModifiers::new(&rules.modifiers);
+    // big cells leave fewer of them on the same board
+    let cell_size = modifiers.cell_size();
+    let rules = RuleOptions {
+        hidden_rows: rules.hidden_rows.div_ceil(cell_size),
+        ..rules.clone()
+    };
+    commands.insert_resource(modifiers);
+    // puzzles bring their own board
+    let puzzle = match (&rules.mode, &rules.puzzle) {
+        (GameMode::Puzzle, Some(puzzle)) => match GameEngine::from_puzzle(puzzle, &rules) {
+            Ok(engine) => Some((puzzle.size(), engine)),
+            Err(e) => {
+                error!("{e}");
+                None
+            }
+        },
+        _ => None,
+    };
+    // only this board has the size of the puzzle, other modes keep the configured size
+    let options = match &puzzle {
+        Some((map_size, _)) => BoardOptions {
+            map_size: *map_size,
+            ..options.clone()
+        },
+        None => options.scaled(cell_size),
+    };
+    let (board, root) = spawn_board(
+        &mut commands,
+        &options,
+        rules.hidden_rows,
+        &window,
+        &board_assets,
+    );
+
+    let engine = match puzzle {
+        Some((_, engine)) => {
+            spawn_map_blocks(&mut commands, root, &board, &rules.pieces, engine.map());
+            engine
+        }
+        None => {
+            let height = options.map_size.1 + rules.hidden_rows;
+            GameEngine::new(options.map_size.0 as usize, height as usize).with_rules(&rules)
+        }
+    };
+    commands.insert_resource(board);
+    commands.insert_resource(engine);
+    commands.insert_resource(Score::default());
 
+    spawn_ewr.send(SpawnEvent);
+}
+
+/// Spawns a sprite for every locked block and wall of the map, e.g. of a loaded game. The current
+/// tetromino is spawned from the active piece of the engine.
+pub(crate) fn spawn_map_blocks(
+    commands: &mut Commands,
+    root: Entity,
+    board: &Board,
+    pieces: &PieceSet,
+    map: &Map,
+) {
+    commands.entity(root).with_children(|parent| {
+        for (coordinates, tile) in map
+            .inner
+            .iter()
+            .filter(|(_, tile)| tile.is_block() || **tile == Tile::Wall)
+        {
+            let symbol = match tile {
+                Tile::Block(c) => *c,
+                Tile::Garbage | Tile::Wall => {
+                    let (color, name) = match tile {
+                        Tile::Wall => (WALL_COLOR, "Wall"),
+                        _ => (GARBAGE_COLOR, "Garbage"),
+                    };
+                    let mut block = parent.spawn((
+                        Sprite {
+                            color,
+                            custom_size: Some(Vec2::splat(board.tile_size)),
+                            ..Default::default()
+                        },
+                        board.calc_transform(coordinates),
+                        board.visibility(coordinates),
+                        Name::new(name),
+                        *coordinates,
+                        BaseColor(color),
+                        Block {},
+                    ));
+                    if *tile == Tile::Wall {
+                        block.insert(Wall);
+                    }
+                    continue;
+                }
+                Tile::Empty | Tile::CurrentTetromino(_) => panic!("unexpected tile type"),
+            };
+            let Some(shape) = pieces.shape_type(symbol) else {
+                warn!("{symbol} is not part of the piece set");
+                continue;
+            };
+            parent.spawn((
+                Sprite {
+                    color: pieces.color(shape),
+                    custom_size: Some(Vec2::splat(board.tile_size)),
+                    ..Default::default()
+                },
+                board.calc_transform(coordinates),
+                board.visibility(coordinates),
+                Name::new(format!("Block ({:?})", shape)),
+                *coordinates,
+                ShapePosition {
+                    x: coordinates.x as i16,
+                    y: coordinates.y as i16,
+                },
+                Tetromino {
+                    color: pieces.color(shape),
+                    tetromino_type: shape,
+                    index: ShapePosition {
+                        x: coordinates.x as i16,
+                        y: coordinates.y as i16,
+                    },
+                },
+                Block {},
+            ));
+        }
+    });
+}
+
+/// Spawns the board with the map size of the options, the hidden rows above it are not drawn.
+/// Returns the board and its tile map root, which is the parent of all block sprites.
+pub(crate) fn spawn_board(
+    commands: &mut Commands,
+    options: &BoardOptions,
+    hidden_rows: u16,
+    window: &Query<&Window, With<PrimaryWindow>>,
+    board_assets: &BoardAssets,
+) -> (Board, Entity) {
     // We define the size of our tiles in world space
     let tile_size = match options.tile_size {
         TileSize::Fixed(v) => v,
         TileSize::Adaptive { min, max } => {
-            crate::window::adaptative_tile_size(window.single(), (min, max), &options)
+            crate::window::adaptative_tile_size(window.single(), (min, max), options)
         }
     };
     log::info!("tile size is {}", tile_size);
@@ -44,6 +181,7 @@ pub fn create_board(
         BoardPosition::Custom(p) => p,
     };
 
+    let mut root = None;
     let board_entity = commands
         .spawn((
             Name::new("Board"),
@@ -56,17 +194,21 @@ pub fn create_board(
             },
         ))
         .with_children(|parent| {
-            parent.spawn((
-                Name::new("TileMap"),
-                TileMapRoot,
-                Transform::default(),
-                Sprite {
-                    // image: board_assets.board_material.texture.clone(),
-                    color: BLUE.into(),
-                    custom_size: Some(board_size),
-                    ..Default::default()
-                },
-            ));
+            root = Some(
+                parent
+                    .spawn((
+                        Name::new("TileMap"),
+                        TileMapRoot,
+                        Transform::default(),
+                        Sprite {
+                            // image: board_assets.board_material.texture.clone(),
+                            color: BLUE.into(),
+                            custom_size: Some(board_size),
+                            ..Default::default()
+                        },
+                    ))
+                    .id(),
+            );
             // We spawn the board background sprite at the center of the board, since the sprite pivot is centered
             parent
                 .spawn((
@@ -88,7 +230,7 @@ pub fn create_board(
                         options.map_size.0,
                         tile_size,
                         options.tile_padding,
-                        &board_assets,
+                        board_assets,
                         board_position.clone(),
                     );
                     // spawn_tiles(
@@ -104,22 +246,16 @@ pub fn create_board(
         })
         .id();
 
-    commands.insert_resource(Board {
+    let board = Board {
         entity: board_entity,
         tile_size,
+        hidden_rows,
         bounds: Bounds2 {
             position: board_position.xy(),
             size: board_size,
         },
-    });
-
-    commands.insert_resource(Map::new(
-        options.map_size.0 as usize,
-        options.map_size.1 as usize,
-    ));
-    commands.insert_resource(Score(0));
-
-    spawn_ewr.send(SpawnEvent);
+    };
+    (board, root.expect("tile map root is spawned"))
 }
 
 fn spawn_tiles_at_background(
@@ -0,0 +1,71 @@
+use bevy::{
+    color::palettes::css::{GOLD, LIME},
+    prelude::*,
+};
+
+use crate::{BoardAssets, CalloutRef, GameEvent};
+
+/// How long a callout stays on screen
+const CALLOUT_SECONDS: f32 = 1.5;
+
+/// Shows spins, tetrises, back-to-back clears and combos above the board. Perfect clears get a
+/// bigger popup.
+pub(crate) fn show_callout(
+    mut commands: Commands,
+    mut game_event_rdr: EventReader<GameEvent>,
+    callouts: Query<Entity, With<CalloutRef>>,
+    board_assets: Res<BoardAssets>,
+) {
+    let mut perfect_clear = false;
+    let mut text = None;
+    for event in game_event_rdr.read() {
+        match event {
+            GameEvent::PerfectClear { .. } => perfect_clear = true,
+            GameEvent::Cleared(result) => text = result.callout().or(text),
+            _ => (),
+        }
+    }
+    let Some(text) = text else {
+        return;
+    };
+    let (font_size, color) = if perfect_clear {
+        (45.0, LIME)
+    } else {
+        (30.0, GOLD)
+    };
+    for entity in callouts.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            top: Val::Percent(30.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        Text::new(text),
+        TextLayout::new_with_justify(JustifyText::Center),
+        TextFont {
+            font: board_assets.font.clone(),
+            font_size,
+            ..Default::default()
+        },
+        TextColor(color.into()),
+        ZIndex(1500),
+        CalloutRef(Timer::from_seconds(CALLOUT_SECONDS, TimerMode::Once)),
+        Name::new("Callout"),
+    ));
+}
+
+pub(crate) fn expire_callouts(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut callouts: Query<(Entity, &mut CalloutRef)>,
+) {
+    for (entity, mut callout) in callouts.iter_mut() {
+        if callout.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use bevy::prelude::*;
+use states::GameLogicState;
+
+use crate::{ActiveModifiers, DropEvent, GameEngine, GameEvent};
+
+pub(crate) fn drop(
+    mut engine: ResMut<GameEngine>,
+    modifiers: Res<ActiveModifiers>,
+    mut drop_event_rdr: EventReader<DropEvent>,
+    mut game_event_ewr: EventWriter<GameEvent>,
+    mut logic_state: ResMut<NextState<GameLogicState>>,
+) {
+    for event in drop_event_rdr.read() {
+        let events = engine.apply(modifiers.input((*event).into()));
+        debug!("drop result {:?}", events);
+        if events.iter().any(|e| matches!(e, GameEvent::Locked { .. })) {
+            logic_state.set(GameLogicState::Spawning);
+        }
+        game_event_ewr.send_batch(events);
+    }
+}
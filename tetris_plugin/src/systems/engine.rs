@@ -1 +1,224 @@
+use std::collections::BTreeMap;
 
+use bevy::prelude::*;
+
+use crate::{
+    ActiveModifiers, BaseColor, Block, Board, Coordinates, CurrentTetromino, GameEngine, GameEvent,
+    GameOverEvent, HandlingSettings, HoldPiece, Level, LockedAt, PieceQueue, PieceSet, Score,
+    ScoreEvent, ShapeType, Tetromino, TickCounter, Tile, TileMapRoot, Transitions, GARBAGE_COLOR,
+};
+
+/// Renders the results of the [`GameEngine`]. Keeps one sprite per block in sync with the map.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn apply_game_events(
+    mut commands: Commands,
+    board: Res<Board>,
+    engine: Res<GameEngine>,
+    modifiers: Res<ActiveModifiers>,
+    time: Res<Time>,
+    mut events: EventReader<GameEvent>,
+    current: Query<Entity, With<CurrentTetromino>>,
+    blocks: Query<(Entity, &Coordinates), With<Block>>,
+    root: Query<Entity, With<TileMapRoot>>,
+    mut score_ewr: EventWriter<ScoreEvent>,
+    mut game_over_ewr: EventWriter<GameOverEvent>,
+) {
+    // commands are deferred, so track the entities touched by earlier events of this frame
+    let mut current_entities: Vec<Entity> = current.iter().collect();
+    let mut block_entities: BTreeMap<Coordinates, Entity> =
+        blocks.iter().map(|(e, c)| (*c, e)).collect();
+    let mut animations: Vec<(Coordinates, Coordinates)> = vec![];
+
+    for event in events.read() {
+        match event {
+            GameEvent::Spawned { shape_type, cells } => {
+                let root = root.get_single().expect("TileMapRoot must exist");
+                current_entities = spawn_current_blocks(
+                    &mut commands,
+                    root,
+                    &board,
+                    &engine.rules().pieces,
+                    *shape_type,
+                    cells,
+                );
+                for entity in current_entities.iter() {
+                    modifiers.on_spawn(&mut commands.entity(*entity), *shape_type);
+                }
+            }
+            GameEvent::Moved { cells } | GameEvent::Rotated { cells } => {
+                for (entity, coordinates) in current_entities.iter().zip(cells) {
+                    commands.entity(*entity).insert(*coordinates);
+                }
+            }
+            GameEvent::Held { .. } => {
+                for entity in current_entities.drain(..) {
+                    commands.entity(entity).despawn_recursive();
+                }
+            }
+            GameEvent::Locked { cells } => {
+                for (entity, coordinates) in current_entities.drain(..).zip(cells) {
+                    let mut block = commands.entity(entity);
+                    block.remove::<CurrentTetromino>().insert((
+                        *coordinates,
+                        Block {},
+                        LockedAt(time.elapsed()),
+                    ));
+                    modifiers.on_lock(&mut block);
+                    block_entities.insert(*coordinates, entity);
+                }
+            }
+            GameEvent::LinesCleared { lines, transitions } => {
+                block_entities.retain(|coordinates, entity| {
+                    // walls are never cleared
+                    let deleted = lines.contains(&coordinates.y)
+                        && engine.map().get(coordinates) != Some(&Tile::Wall);
+                    if deleted {
+                        commands.entity(*entity).despawn_recursive();
+                    }
+                    !deleted
+                });
+                let moved: Vec<(Coordinates, Entity)> = transitions
+                    .iter()
+                    .filter_map(|(from, to)| block_entities.remove(from).map(|e| (*to, e)))
+                    .collect();
+                block_entities.extend(moved);
+
+                // cascades clear several times per lock, the animation starts at the sprite
+                // positions of the last frame
+                animations.retain(|(_, to)| !lines.contains(&to.y));
+                let previous = animations.clone();
+                for (from, to) in transitions.iter() {
+                    match previous.iter().position(|(_, t)| t == from) {
+                        Some(index) => animations[index].1 = *to,
+                        None => animations.push((*from, *to)),
+                    }
+                }
+            }
+            GameEvent::GarbageAdded { cells, transitions } => {
+                let root = root.get_single().expect("TileMapRoot must exist");
+                // blocks in the top row are pushed off, walls never move
+                block_entities.retain(|coordinates, entity| {
+                    let pushed_off =
+                        coordinates.y == 0 && engine.map().get(coordinates) != Some(&Tile::Wall);
+                    if pushed_off {
+                        commands.entity(*entity).despawn_recursive();
+                    }
+                    !pushed_off
+                });
+                let raised: Vec<(Coordinates, Entity)> = transitions
+                    .iter()
+                    .filter_map(|(from, to)| block_entities.remove(from).map(|e| (*to, e)))
+                    .collect();
+                for (coordinates, entity) in raised {
+                    // blocks pushed into the hidden rows are no longer drawn
+                    commands
+                        .entity(entity)
+                        .insert((coordinates, board.visibility(&coordinates)));
+                    block_entities.insert(coordinates, entity);
+                }
+                for coordinates in cells {
+                    let entity = commands
+                        .spawn((
+                            Sprite {
+                                color: GARBAGE_COLOR,
+                                custom_size: Some(Vec2::splat(board.tile_size)),
+                                ..Default::default()
+                            },
+                            board.calc_transform(coordinates),
+                            Name::new("Garbage"),
+                            *coordinates,
+                            BaseColor(GARBAGE_COLOR),
+                            Block {},
+                        ))
+                        .id();
+                    commands.entity(root).add_child(entity);
+                    block_entities.insert(*coordinates, entity);
+                }
+            }
+            GameEvent::Score(points) => {
+                score_ewr.send(ScoreEvent(*points));
+            }
+            GameEvent::GameOver(reason) => {
+                game_over_ewr.send(GameOverEvent(*reason));
+            }
+            GameEvent::Cleared(_) | GameEvent::PerfectClear { .. } => {
+                // Handled in show_callout system
+            }
+            GameEvent::LevelChanged(level) => {
+                info!("level {}", level);
+            }
+            GameEvent::Dropped { .. } => {
+                // Points arrive as a separate Score event
+            }
+            GameEvent::Collision(_) => {
+                // Handled in play_error_sound system
+            }
+        }
+    }
+    if !animations.is_empty() {
+        // TODO it might be better to use components add attach them to the entities being moved instead of using a single resource. Depends on animation implementation
+        commands.insert_resource(Transitions(animations));
+    }
+}
+
+/// Spawns the sprites of the current tetromino, in the order of the cells of the engine
+pub(crate) fn spawn_current_blocks(
+    commands: &mut Commands,
+    root: Entity,
+    board: &Board,
+    pieces: &PieceSet,
+    shape_type: ShapeType,
+    cells: &[Coordinates],
+) -> Vec<Entity> {
+    cells
+        .iter()
+        .zip(Tetromino::blocks_from_type(pieces, shape_type))
+        .map(|(coordinates, block)| {
+            let entity = commands
+                .spawn((
+                    Sprite {
+                        color: block.color,
+                        custom_size: Some(Vec2::splat(board.tile_size)),
+                        ..Default::default()
+                    },
+                    board.calc_transform(coordinates),
+                    CurrentTetromino {},
+                    Name::new(format!("Current ({:?})", block.tetromino_type)),
+                    *coordinates,
+                    block.index,
+                    block,
+                ))
+                .id();
+            commands.entity(root).add_child(entity);
+            entity
+        })
+        .collect()
+}
+
+/// Handling settings belong to the player, so they survive a new or loaded game
+pub(crate) fn apply_handling(settings: Res<HandlingSettings>, mut engine: ResMut<GameEngine>) {
+    if settings.is_changed() || engine.is_added() {
+        engine.set_soft_drop_factor(settings.soft_drop_factor);
+    }
+}
+
+/// Mirrors the engine state into the resources used by the ui.
+pub(crate) fn sync_engine(
+    engine: Res<GameEngine>,
+    mut score: ResMut<Score>,
+    mut ticks: ResMut<TickCounter>,
+    mut piece_queue: ResMut<PieceQueue>,
+    mut hold_piece: ResMut<HoldPiece>,
+    mut level: ResMut<Level>,
+) {
+    if engine.is_changed() {
+        score.set_if_neq(*engine.score());
+        ticks.0 = engine.ticks();
+        level.set_if_neq(Level(engine.level()));
+        piece_queue.set_if_neq(PieceQueue(engine.preview()));
+        hold_piece.set_if_neq(HoldPiece {
+            shape_type: engine.hold(),
+            used: !engine.can_hold(),
+        });
+    }
+}
@@ -1,7 +1,7 @@
 use bevy::prelude::*;
 use states::GameStatus;
 
-use crate::{GameCommand, PopupText};
+use crate::{GameCommand, GameEngine, GameEvent, PopupText};
 
 pub(crate) fn events_to_state(
     mut commands: Commands,
@@ -33,16 +33,35 @@ pub(crate) fn events_to_state(
             GameCommand::Load => {
                 // Handled in load_and_save system
             }
+            GameCommand::IncreaseSpeed | GameCommand::DecreaseSpeed => {
+                // Handled in change_speed system
+            }
             _ => info!("do nothing with command {:?}", event),
         }
     }
 }
 
+/// Speed commands move the level up or down, the gravity curve follows
+pub(crate) fn change_speed(
+    mut engine: ResMut<GameEngine>,
+    mut game_command: EventReader<GameCommand>,
+    mut game_event_ewr: EventWriter<GameEvent>,
+) {
+    for event in game_command.read() {
+        let level = match event {
+            GameCommand::IncreaseSpeed => engine.level() + 1,
+            GameCommand::DecreaseSpeed => engine.level().saturating_sub(1),
+            _ => continue,
+        };
+        game_event_ewr.send_batch(engine.set_level(level));
+    }
+}
+
 #[cfg(test)]
 mod unit_tests {
     use super::*;
     use bevy::state::app::StatesPlugin;
-    use states::{AppState, InGame};
+    use states::{AppState, GameLogicState, InGame};
 
     #[test]
     fn events_to_state_test() {
@@ -1,22 +1,38 @@
-use bevy::{color::palettes::css::RED, prelude::*};
+use bevy::{
+    color::palettes::css::{GREEN, RED},
+    prelude::*,
+};
 
 use states::GameStatus;
 
-use crate::{BoardAssets, GameOverEvent};
+use crate::{format_time, BoardAssets, GameEngine, GameOverEvent};
 
 pub fn gameover(
     mut commands: Commands,
     mut game_over_event_rdr: EventReader<GameOverEvent>,
     mut state: ResMut<NextState<GameStatus>>,
     board_assets: Res<BoardAssets>,
+    engine: Option<Res<GameEngine>>,
 ) {
     let event = game_over_event_rdr.read().next();
-    if event.is_some() {
+    if let Some(GameOverEvent(reason)) = event {
         state.set(GameStatus::Gameover);
 
+        let (title, color) = if reason.is_finished() {
+            ("FINISHED", GREEN)
+        } else {
+            ("GAME OVER", RED)
+        };
+        // races are ranked by their completion time
+        let result = match engine {
+            Some(engine) if reason.is_finished() && engine.rules().mode.is_race() => {
+                format_time(engine.elapsed())
+            }
+            _ => reason.to_string(),
+        };
         commands.spawn((
-            Text2d("GAME OVER".to_string()),
-            TextColor(RED.into()),
+            Text2d(format!("{title}\n{result}")),
+            TextColor(color.into()),
             TextLayout {
                 justify: JustifyText::Center,
                 linebreak: LineBreak::WordBoundary,
@@ -29,6 +45,6 @@ pub fn gameover(
             Transform::from_xyz(0., 0., 1000.),
         ));
 
-        error!("gameover");
+        info!("gameover: {reason}");
     }
 }
@@ -0,0 +1,63 @@
+use bevy::prelude::*;
+
+use crate::{
+    ActiveModifiers, BlockState, Board, BoardOptions, Coordinates, GameEngine, GhostBlock,
+    TileMapRoot,
+};
+
+/// Projects the current tetromino down to its landing position as translucent sprites
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn update_ghost(
+    mut commands: Commands,
+    engine: Res<GameEngine>,
+    modifiers: Res<ActiveModifiers>,
+    options: Res<BoardOptions>,
+    board: Res<Board>,
+    ghosts: Query<Entity, With<GhostBlock>>,
+    root: Query<Entity, With<TileMapRoot>>,
+    mut last: Local<Vec<Coordinates>>,
+) {
+    if !engine.is_changed() && !options.is_changed() {
+        return;
+    }
+    let cells = match engine.active() {
+        Some(_) if options.ghost_piece => engine.ghost(),
+        _ => vec![],
+    };
+    if *last == cells && !options.is_changed() {
+        return;
+    }
+    for entity in ghosts.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    let (Some(piece), Ok(root)) = (engine.active(), root.get_single()) else {
+        return;
+    };
+    let color = modifiers
+        .block_color(
+            engine.rules().pieces.color(piece.shape_type),
+            BlockState::Active,
+        )
+        .with_alpha(0.3);
+    for coordinates in cells.iter() {
+        let mut transform = board.calc_transform(coordinates);
+        let visibility = board.visibility(coordinates);
+        // below the current tetromino
+        transform.translation.z -= 10.0;
+        let entity = commands
+            .spawn((
+                Sprite {
+                    color,
+                    custom_size: Some(Vec2::splat(board.tile_size)),
+                    ..Default::default()
+                },
+                transform,
+                visibility,
+                GhostBlock,
+                Name::new("Ghost"),
+            ))
+            .id();
+        commands.entity(root).add_child(entity);
+    }
+    *last = cells;
+}
@@ -0,0 +1,45 @@
+use bevy::prelude::*;
+
+use crate::{
+    spawn_shape_grid, ActiveModifiers, GameEngine, GameEvent, HoldEvent, HoldPiece, HoldRef,
+};
+
+pub(crate) fn hold(
+    mut engine: ResMut<GameEngine>,
+    modifiers: Res<ActiveModifiers>,
+    mut hold_event_rdr: EventReader<HoldEvent>,
+    mut game_event_ewr: EventWriter<GameEvent>,
+) {
+    for event in hold_event_rdr.read() {
+        let events = engine.apply(modifiers.input((*event).into()));
+        debug!("hold result {:?}", events);
+        game_event_ewr.send_batch(events);
+    }
+}
+
+/// Draws the held shape into the left sidebar. It is dimmed while hold cannot be used.
+pub(crate) fn update_hold(
+    mut commands: Commands,
+    engine: Res<GameEngine>,
+    modifiers: Res<ActiveModifiers>,
+    hold_piece: Res<HoldPiece>,
+    slot: Query<Entity, With<HoldRef>>,
+) {
+    let Ok(slot) = slot.get_single() else {
+        return;
+    };
+    commands.entity(slot).despawn_descendants();
+    if let Some(shape_type) = hold_piece.shape_type {
+        commands.entity(slot).with_children(|parent| {
+            spawn_shape_grid(
+                parent,
+                Name::new("Hold"),
+                Val::Percent(100.0),
+                &engine.rules().pieces,
+                shape_type,
+                hold_piece.used,
+                &modifiers,
+            );
+        });
+    }
+}
@@ -1,11 +1,100 @@
-use bevy::{prelude::*, utils::info};
+use bevy::{
+    prelude::*,
+    utils::{info, SystemTime},
+    window::PrimaryWindow,
+};
 use bevy_pkv::PkvStore;
 
 use crate::{
-    Board, CurrentTetromino, GameCommand, Map, ShapePosition, ShapeType, Tetromino, Tile,
+    spawn_board, spawn_current_blocks, spawn_map_blocks, ActiveModifiers, Board, BoardAssets,
+    BoardOptions, GameCommand, GameEngine, GameMode, RuleOptions, SaveSlot, SaveSlots, SlotCommand,
     TileMapRoot, Transitions,
 };
 
+/// Store key of the slot names, each slot is stored under [`slot_key`]
+const SLOTS_KEY: &str = "save_slots";
+/// Store key of the single save of older versions, it is migrated to a slot
+const LEGACY_KEY: &str = "savegame";
+
+fn slot_key(name: &str) -> String {
+    format!("slot:{name}")
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+fn store_slot(pkv: &mut PkvStore, slots: &mut SaveSlots, slot: SaveSlot) {
+    let key = slot_key(&slot.name);
+    slots.insert(slot.clone());
+    pkv.set_string(key, &slot.to_ron())
+        .expect("failed to store save slot");
+    pkv.set(SLOTS_KEY, &slots.names())
+        .expect("failed to store save slots");
+}
+
+fn delete_slot(pkv: &mut PkvStore, slots: &mut SaveSlots, name: &str) {
+    if slots.remove(name).is_none() {
+        warn!("save slot {name} not found");
+        return;
+    }
+    if let Err(e) = pkv.remove(slot_key(name)) {
+        warn!("failed to remove save slot {name}: {e}");
+    }
+    pkv.set(SLOTS_KEY, &slots.names())
+        .expect("failed to store save slots");
+}
+
+/// Reads the save slots from the store and turns the save of older versions into a slot
+pub(crate) fn load_save_slots(
+    mut commands: Commands,
+    mut pkv: ResMut<PkvStore>,
+    rules: Res<RuleOptions>,
+) {
+    let mut slots = SaveSlots::default();
+    for name in pkv.get::<Vec<String>>(SLOTS_KEY).unwrap_or_default() {
+        match pkv
+            .get::<String>(slot_key(&name))
+            .map(|text| SaveSlot::parse(&text))
+        {
+            Ok(Ok(slot)) => slots.insert(slot),
+            Ok(Err(e)) => error!("save slot {name}: {e}"),
+            Err(e) => error!("save slot {name}: {e}"),
+        }
+    }
+    if let Ok(text) = pkv.get::<String>(LEGACY_KEY) {
+        let name = slots.unique_name("Slot 1");
+        match SaveSlot::migrate(name, &text, &rules, now()) {
+            Ok(slot) => {
+                info!("migrated savegame to slot {}", slot.name);
+                let name = slot.name.clone();
+                store_slot(&mut pkv, &mut slots, slot);
+                // the old save is the only copy until the slot reads back as a playable game
+                let stored = pkv
+                    .get::<String>(slot_key(&name))
+                    .map_err(|e| e.to_string())
+                    .and_then(|text| SaveSlot::parse(&text).map_err(|e| e.to_string()))
+                    .and_then(|slot| slot.game(&rules).map_err(|e| e.to_string()));
+                match stored {
+                    Ok(_) => {
+                        if let Err(e) = pkv.remove(LEGACY_KEY) {
+                            warn!("failed to remove the old savegame: {e}");
+                        }
+                    }
+                    Err(e) => {
+                        error!("migrated save slot {name}: {e}, keeping the old savegame");
+                        delete_slot(&mut pkv, &mut slots, &name);
+                    }
+                }
+            }
+            Err(e) => error!("old savegame: {e}"),
+        }
+    }
+    commands.insert_resource(slots);
+}
+
 pub(crate) fn load_and_save_warning(mut game_command: EventReader<crate::GameCommand>) {
     for event in game_command.read() {
         if event == &GameCommand::Save || event == &GameCommand::Load {
@@ -13,82 +102,171 @@ pub(crate) fn load_and_save_warning(mut game_command: EventReader<crate::GameCom
         }
     }
 }
+
+/// Slot commands of the pause menu. The save and load keys save to a new slot and load the
+/// newest one.
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn load_and_save(
     mut commands: Commands,
     board: Res<Board>,
-    map: Res<Map>,
+    mut options: ResMut<BoardOptions>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    board_assets: Res<BoardAssets>,
+    engine: Res<GameEngine>,
     transitions: Option<Res<Transitions>>,
     mut game_command: EventReader<crate::GameCommand>,
+    mut slot_command: EventReader<SlotCommand>,
     root: Query<Entity, With<TileMapRoot>>,
+    parents: Query<&Parent>,
     mut pkv: ResMut<PkvStore>,
+    mut slots: ResMut<SaveSlots>,
 ) {
-    for event in game_command.read() {
+    let keys = game_command.read().filter_map(|event| match event {
+        GameCommand::Save => Some(SlotCommand::Save),
+        GameCommand::Load => match slots.newest() {
+            Some(slot) => Some(SlotCommand::Load(slot.name.clone())),
+            None => {
+                error!("no save slot found");
+                None
+            }
+        },
+        _ => None,
+    });
+    let requests: Vec<_> = keys.chain(slot_command.read().cloned()).collect();
+    for event in requests {
         info!("load_and_save {:?}", event);
-        // state.set(pause_state.clone()).unwrap();
-        if event == &GameCommand::Save {
-            info("save");
-            if transitions.is_none() {
-                pkv.set_string("savegame", map.as_savegame_string().as_str())
-                    .expect("failed to store username");
-            } else {
-                warn!("cannot save while transitions is going on");
+        match event {
+            SlotCommand::Save => {
+                info("save");
+                if transitions.is_some() {
+                    warn!("cannot save while transitions is going on");
+                    continue;
+                }
+                let name = slots.unique_name(&format!("Slot {}", slots.0.len() + 1));
+                let slot = SaveSlot::new(name, &engine, now());
+                store_slot(&mut pkv, &mut slots, slot);
             }
-        } else if event == &GameCommand::Load {
-            info("load");
-            // TODO DESPAWN OLD STUFF
-            commands.entity(root.single()).despawn_descendants();
-
-            if let Ok(res) = pkv.get::<String>("savegame") {
-                info!("map loaded {res}");
-                let map = Map::from_str(&res);
+            SlotCommand::Delete(name) => delete_slot(&mut pkv, &mut slots, &name),
+            #[cfg(not(target_arch = "wasm32"))]
+            SlotCommand::Export(name, directory) => {
+                let Some(slot) = slots.get(&name) else {
+                    error!("save slot {name} not found");
+                    continue;
+                };
+                if let Err(e) = std::fs::create_dir_all(&directory) {
+                    error!("{}: {e}", directory.display());
+                    continue;
+                }
+                let path = crate::export_path(&directory, slot);
+                match slot.export(&path) {
+                    Ok(()) => info!("exported save slot {name} to {}", path.display()),
+                    Err(e) => error!("{}: {e}", path.display()),
+                }
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            SlotCommand::Import(path) => {
+                let files = match std::fs::read_dir(&path) {
+                    Ok(entries) => entries
+                        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                        .filter(|file| file.extension().is_some_and(|ext| ext == "ron"))
+                        .collect(),
+                    Err(_) => vec![path],
+                };
+                for file in files {
+                    match SaveSlot::import(&file) {
+                        Ok(slot) if slots.contains(&slot) => {
+                            info!("{} is imported already", file.display());
+                        }
+                        Ok(mut slot) => {
+                            slot.name = slots.unique_name(&slot.name);
+                            info!("imported {} as save slot {}", file.display(), slot.name);
+                            store_slot(&mut pkv, &mut slots, slot);
+                        }
+                        Err(e) => error!("{}: {e}", file.display()),
+                    }
+                }
+            }
+            SlotCommand::Load(name) => {
+                info("load");
+                let Some(slot) = slots.get(&name) else {
+                    error!("save slot {name} not found");
+                    continue;
+                };
+                let loaded = match slot.game(engine.rules()) {
+                    Ok(loaded) => loaded,
+                    Err(e) => {
+                        error!("{e}");
+                        continue;
+                    }
+                };
+                info!("game loaded at tick {}", loaded.ticks());
+                commands.entity(root.single()).despawn_descendants();
 
                 #[cfg(feature = "debug")]
-                bevy::log::info!("{}", map);
+                bevy::log::info!("{}", loaded.map());
 
-                commands.entity(root.single()).with_children(|parent| {
-                    for (coordinates, tile) in map
-                        .inner
-                        .iter()
-                        .filter(|(_, tile)| tile.is_block() || tile.is_moveable())
-                    {
-                        let shape = if let Tile::CurrentTetromino(c) = tile {
-                            ShapeType::from_char(*c)
-                        } else if let Tile::Block(c) = tile {
-                            ShapeType::from_char(*c)
-                        } else {
-                            panic!("unexpected tile type");
-                        };
-                        let mut entity = parent.spawn((
-                            Sprite {
-                                color: shape.get_color(),
-                                custom_size: Some(Vec2::splat(board.tile_size)),
-                                ..Default::default()
-                            },
-                            board.calc_transform(&coordinates),
-                            Name::new(format!("Block ({:?})", shape)),
-                            coordinates.clone(),
-                            ShapePosition {
-                                x: coordinates.x as i16,
-                                y: coordinates.y as i16,
-                            },
-                            Tetromino {
-                                color: shape.get_color(),
-                                tetromino_type: shape,
-                                index: ShapePosition {
-                                    x: coordinates.x as i16,
-                                    y: coordinates.y as i16,
-                                },
-                            },
-                        ));
-                        if let Tile::CurrentTetromino(_) = tile {
-                            entity.insert(CurrentTetromino {});
-                        }
+                // a savegame of another size gets a new board
+                let rules = loaded.rules();
+                let modifiers = ActiveModifiers::new(&rules.modifiers);
+                let cell_size = modifiers.cell_size();
+                let map = loaded.map();
+                let map_size = (map.width as u16, map.height as u16 - rules.hidden_rows);
+                let mut root = root.single();
+                let mut new_board = None;
+                // compare with the board of the running game, a puzzle board is smaller than
+                // the one of the options
+                let current = engine.map();
+                let current_size = (
+                    current.width as u16,
+                    current.height as u16 - engine.rules().hidden_rows,
+                );
+                let current_cell_size = ActiveModifiers::new(&engine.rules().modifiers).cell_size();
+                if map_size != current_size
+                    || cell_size != current_cell_size
+                    || rules.hidden_rows != engine.rules().hidden_rows
+                {
+                    info!("resizing board to {:?}", map_size);
+                    let resized_options = BoardOptions {
+                        map_size: (map_size.0 * cell_size, map_size.1 * cell_size),
+                        ..options.clone()
+                    };
+                    // the size of a puzzle only applies to its own board
+                    if rules.mode != GameMode::Puzzle {
+                        *options = resized_options.clone();
                     }
-                });
+                    // the board is the parent of the tile map root
+                    if let Ok(board) = parents.get(root) {
+                        commands.entity(board.get()).despawn_recursive();
+                    }
+                    let (resized, resized_root) = spawn_board(
+                        &mut commands,
+                        &resized_options.scaled(cell_size),
+                        rules.hidden_rows,
+                        &window,
+                        &board_assets,
+                    );
+                    root = resized_root;
+                    new_board = Some(resized);
+                }
+                let board = new_board.as_ref().unwrap_or(&board);
+
+                spawn_map_blocks(&mut commands, root, board, &rules.pieces, map);
+                if let Some(piece) = loaded.active() {
+                    spawn_current_blocks(
+                        &mut commands,
+                        root,
+                        board,
+                        &rules.pieces,
+                        piece.shape_type,
+                        &piece.cells(),
+                    );
+                }
 
-                commands.insert_resource(map);
-            } else {
-                error!("savegame not found");
+                commands.insert_resource(modifiers);
+                commands.insert_resource(loaded);
+                if let Some(board) = new_board {
+                    commands.insert_resource(board);
+                }
             }
         }
     }
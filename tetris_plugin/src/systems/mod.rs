@@ -8,8 +8,12 @@ mod move_tiles;
 pub(crate) use move_tiles::*;
 mod rotate;
 pub(crate) use rotate::*;
-mod line;
-pub(crate) use line::*;
+mod hold;
+pub(crate) use hold::*;
+mod drop;
+pub(crate) use drop::*;
+mod ghost;
+pub(crate) use ghost::*;
 mod load_and_save;
 pub(crate) use load_and_save::*;
 mod engine;
@@ -24,19 +28,19 @@ pub(crate) use animate_line_del::animate;
 // #[cfg(feature = "debug")]
 // pub(crate) use spawn_debug_tile::spawn_debug_block;
 
-mod score;
-pub(crate) use score::score;
 mod board;
 pub(crate) use board::*;
 mod popup;
 pub(crate) use popup::*;
 mod game_command;
-pub(crate) use game_command::events_to_state;
+pub(crate) use game_command::{change_speed, events_to_state};
 mod collision;
 pub(crate) use collision::*;
-mod cleaning;
-pub(crate) use cleaning::convert_to_block;
 mod tetromino;
 pub(crate) use tetromino::*;
 mod sidebar;
 pub(crate) use sidebar::*;
+mod callout;
+pub(crate) use callout::*;
+mod save_slots;
+pub(crate) use save_slots::*;
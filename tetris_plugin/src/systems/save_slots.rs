@@ -0,0 +1,245 @@
+use bevy::{prelude::*, utils::SystemTime};
+
+use crate::{BoardAssets, SaveSlot, SaveSlotMenu, SaveSlots, SlotButton, SlotCommand};
+
+const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
+const HOVERED_BUTTON: Color = Color::srgb(0.25, 0.25, 0.25);
+const PRESSED_BUTTON: Color = Color::srgb(0.35, 0.75, 0.35);
+const TEXT_COLOR: Color = Color::srgb(0.9, 0.9, 0.9);
+
+/// How long ago the slot was saved, e.g. `5 min ago`
+pub(crate) fn age(timestamp: u64, now: u64) -> String {
+    match now.saturating_sub(timestamp) {
+        0..60 => "just now".to_string(),
+        secs @ 60..3600 => format!("{} min ago", secs / 60),
+        secs @ 3600..86400 => format!("{} h ago", secs / 3600),
+        secs => format!("{} days ago", secs / 86400),
+    }
+}
+
+/// Rebuilds the slot list of the pause menu when it is missing or the slots changed
+pub(crate) fn update_save_slot_menu(
+    mut commands: Commands,
+    slots: Res<SaveSlots>,
+    menu: Query<Entity, With<SaveSlotMenu>>,
+    board_assets: Res<BoardAssets>,
+    #[cfg(not(target_arch = "wasm32"))] directory: Res<crate::SlotDirectory>,
+) {
+    if !menu.is_empty() && !slots.is_changed() {
+        return;
+    }
+    for entity in menu.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let font = board_assets.font.clone();
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(10.0),
+                width: Val::Percent(100.0),
+                flex_wrap: FlexWrap::Wrap,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::FlexEnd,
+                column_gap: Val::Px(10.0),
+                row_gap: Val::Px(10.0),
+                ..default()
+            },
+            SaveSlotMenu,
+            ZIndex(2001),
+            Name::new("SaveSlotMenu"),
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(Node {
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(5.0),
+                    ..default()
+                })
+                .with_children(|column| {
+                    spawn_button(column, "Save", SlotCommand::Save, &font);
+                    #[cfg(not(target_arch = "wasm32"))]
+                    spawn_button(
+                        column,
+                        "Import",
+                        SlotCommand::Import(directory.0.clone()),
+                        &font,
+                    );
+                });
+            for slot in slots.0.iter() {
+                parent
+                    .spawn((
+                        Node {
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            padding: UiRect::all(Val::Px(5.0)),
+                            row_gap: Val::Px(3.0),
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+                    ))
+                    .with_children(|card| {
+                        spawn_text(card, &slot.name, 18.0, &font);
+                        spawn_text(card, &description(slot, now), 12.0, &font);
+                        spawn_text(card, &slot.thumbnail, 8.0, &font);
+                        card.spawn(Node {
+                            column_gap: Val::Px(5.0),
+                            ..default()
+                        })
+                        .with_children(|buttons| {
+                            let name = &slot.name;
+                            spawn_button(buttons, "Load", SlotCommand::Load(name.clone()), &font);
+                            let delete = SlotCommand::Delete(name.clone());
+                            spawn_button(buttons, "Delete", delete, &font);
+                            #[cfg(not(target_arch = "wasm32"))]
+                            spawn_button(
+                                buttons,
+                                "Export",
+                                SlotCommand::Export(name.clone(), directory.0.clone()),
+                                &font,
+                            );
+                        });
+                    });
+            }
+        });
+}
+
+pub(crate) fn hide_save_slot_menu(mut commands: Commands, menu: Query<Entity, With<SaveSlotMenu>>) {
+    for entity in menu.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+pub(crate) fn save_slot_buttons(
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, &SlotButton),
+        Changed<Interaction>,
+    >,
+    mut slot_command: EventWriter<SlotCommand>,
+) {
+    for (interaction, mut background, button) in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Pressed => {
+                *background = PRESSED_BUTTON.into();
+                slot_command.send(button.0.clone());
+            }
+            Interaction::Hovered => {
+                *background = HOVERED_BUTTON.into();
+            }
+            Interaction::None => {
+                *background = NORMAL_BUTTON.into();
+            }
+        }
+    }
+}
+
+fn description(slot: &SaveSlot, now: u64) -> String {
+    format!(
+        "{} - score {} - level {} - {}",
+        slot.mode,
+        slot.score,
+        slot.level,
+        age(slot.timestamp, now)
+    )
+}
+
+/// File in `directory` the slot is exported to. Slot names may contain characters which are not
+/// allowed in file names, they are replaced by `_`. A counter is appended while the file belongs
+/// to another slot, e.g. to `a b` when `a_b` is exported, a file of the same slot is overwritten.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn export_path(directory: &std::path::Path, slot: &SaveSlot) -> std::path::PathBuf {
+    let stem: String = slot
+        .name
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect();
+    let other_slot = |path: &std::path::Path| {
+        path.exists()
+            && std::fs::read_to_string(path)
+                .ok()
+                .and_then(|text| SaveSlot::parse(&text).ok())
+                .is_none_or(|exported| exported.name != slot.name)
+    };
+    (1..)
+        .map(|n| match n {
+            1 => directory.join(format!("{stem}_{}.ron", slot.timestamp)),
+            n => directory.join(format!("{stem}_{}_{n}.ron", slot.timestamp)),
+        })
+        .find(|path| !other_slot(path))
+        .expect("there is always a free file name")
+}
+
+fn spawn_text(parent: &mut ChildBuilder, text: &str, font_size: f32, font: &Handle<Font>) {
+    parent.spawn((
+        Text::new(text),
+        TextLayout::new_with_justify(JustifyText::Center),
+        TextFont {
+            font: font.clone(),
+            font_size,
+            ..default()
+        },
+        TextColor(TEXT_COLOR),
+    ));
+}
+
+fn spawn_button(parent: &mut ChildBuilder, label: &str, command: SlotCommand, font: &Handle<Font>) {
+    parent
+        .spawn((
+            Button,
+            Node {
+                padding: UiRect::axes(Val::Px(8.0), Val::Px(3.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BorderRadius::all(Val::Px(5.0)),
+            BackgroundColor(NORMAL_BUTTON),
+            SlotButton(command),
+        ))
+        .with_children(|button| spawn_text(button, label, 14.0, font));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_age() {
+        assert_eq!(age(1000, 1030), "just now");
+        assert_eq!(age(1000, 1000 + 5 * 60 + 10), "5 min ago");
+        assert_eq!(age(0, 2 * 3600), "2 h ago");
+        assert_eq!(age(0, 3 * 86400), "3 days ago");
+        // clocks of other machines can be ahead
+        assert_eq!(age(2000, 1000), "just now");
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_export_paths_are_unique() {
+        let slot = |name: &str| SaveSlot {
+            name: name.to_string(),
+            timestamp: 1000,
+            mode: crate::GameMode::Marathon,
+            score: 0,
+            level: 1,
+            thumbnail: String::new(),
+            save: String::new(),
+        };
+        let directory = std::env::temp_dir().join(format!("tetris-export-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let (a, b) = (slot("a b"), slot("a_b"));
+        let path = export_path(&directory, &a);
+        assert_eq!(path, directory.join("a_b_1000.ron"));
+        a.export(&path).unwrap();
+        // saved in the same second, the name only differs in a replaced character
+        let other = export_path(&directory, &b);
+        assert_eq!(other, directory.join("a_b_1000_2.ron"));
+        b.export(&other).unwrap();
+        assert_eq!(export_path(&directory, &a), path);
+        assert_eq!(export_path(&directory, &b), other);
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -1,23 +1,52 @@
+use std::time::Duration;
+
 use bevy::{
     color::palettes::css::{BLACK, DARK_GRAY, DARK_GREEN, GOLD},
     prelude::*,
 };
 
-use crate::{BoardAssets, Score, SidebarRef};
+use crate::{BoardAssets, GameEngine, HoldRef, HudItem, RuleOptions, SidebarRef};
 
+/// Text of one value of the game mode's HUD
 #[derive(Component)]
-pub struct ScoreRef;
+pub struct HudRef(pub HudItem);
 
-pub fn update_score(score: Res<Score>, mut text: Query<&mut Text, With<ScoreRef>>) {
-    if score.is_changed() {
-        info!("score changed");
-        if let Ok(mut text) = text.get_single_mut() {
-            text.0 = format!("Score: {}", score.0);
+pub fn update_hud(engine: Res<GameEngine>, mut texts: Query<(&mut Text, &HudRef)>) {
+    if engine.is_changed() {
+        for (mut text, hud) in texts.iter_mut() {
+            text.0 = hud_text(hud.0, &engine);
         }
     }
 }
 
-pub fn sidebar(mut commands: Commands, score: Res<Score>, board_assets: Res<BoardAssets>) {
+fn hud_text(item: HudItem, engine: &GameEngine) -> String {
+    match item {
+        HudItem::Score => format!("Score: {}", engine.score().total()),
+        HudItem::Level => format!("Level: {}", engine.level()),
+        HudItem::Lines => match engine.rules().mode.line_goal() {
+            Some(goal) => format!("Lines: {}/{}", engine.goal_lines(), goal),
+            None => format!("Lines: {}", engine.lines()),
+        },
+        HudItem::Time => format!("Time: {}", format_time(engine.elapsed())),
+        HudItem::TimeLeft => format!(
+            "Time Left: {}",
+            format_time(engine.time_left().unwrap_or_default())
+        ),
+        HudItem::Goal => match &engine.rules().puzzle {
+            Some(puzzle) => format!("Goal: {}", puzzle.goal),
+            None => String::new(),
+        },
+        HudItem::PiecesLeft => format!("Pieces: {}", engine.pieces_left().unwrap_or_default()),
+    }
+}
+
+/// Minutes, seconds and hundredths, e.g. `1:05.20`
+pub(crate) fn format_time(time: Duration) -> String {
+    let seconds = time.as_secs_f64();
+    format!("{}:{:05.2}", (seconds / 60.0) as u64, seconds % 60.0)
+}
+
+pub fn sidebar(mut commands: Commands, rules: Res<RuleOptions>, board_assets: Res<BoardAssets>) {
     commands
         .spawn((
             Node {
@@ -41,25 +70,44 @@ pub fn sidebar(mut commands: Commands, score: Res<Score>, board_assets: Res<Boar
                 Node {
                     width: Val::Auto,
                     height: Val::Auto,
-                    min_height: Val::Px(100.0),
+                    min_height: Val::Px(30.0),
                     min_width: Val::Px(200.0),
                     position_type: PositionType::Relative,
                     justify_content: JustifyContent::Start,
                     align_items: AlignItems::Start,
                     ..default()
                 },
-                Text::new(format!("Score: {}", score.0)),
+                Text::new(rules.mode.to_string()),
                 TextLayout::new_with_justify(JustifyText::Left),
                 TextFont {
                     font: board_assets.font.clone(),
-                    font_size: 15.0,
+                    font_size: 20.0,
                     ..Default::default()
                 },
-                Name::new("Score"),
-                ScoreRef,
+                Name::new("Mode"),
                 TextColor(GOLD.into()),
-                // BackgroundColor(DARK_GRAY.into()),
             ));
+            for item in rules.mode.hud() {
+                parent.spawn((
+                    Node {
+                        min_width: Val::Px(200.0),
+                        position_type: PositionType::Relative,
+                        justify_content: JustifyContent::Start,
+                        align_items: AlignItems::Start,
+                        ..default()
+                    },
+                    Text::default(),
+                    TextLayout::new_with_justify(JustifyText::Left),
+                    TextFont {
+                        font: board_assets.font.clone(),
+                        font_size: 15.0,
+                        ..Default::default()
+                    },
+                    Name::new(format!("{item:?}")),
+                    HudRef(*item),
+                    TextColor(GOLD.into()),
+                ));
+            }
         });
 }
 pub fn sidebar_left(mut commands: Commands, board_assets: Res<BoardAssets>) {
@@ -82,6 +130,37 @@ pub fn sidebar_left(mut commands: Commands, board_assets: Res<BoardAssets>) {
             Name::new("Sidebar Left"),
         ))
         .with_children(|parent| {
+            parent
+                .spawn((
+                    Node {
+                        grid_column: GridPlacement::span(2),
+                        grid_row: GridPlacement::span(5),
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    Name::new("Hold Slot"),
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        Text::new("Hold"),
+                        TextFont {
+                            font: board_assets.font.clone(),
+                            font_size: 10.0,
+                            ..Default::default()
+                        },
+                        TextColor(GOLD.into()),
+                    ));
+                    parent.spawn((
+                        Node {
+                            width: Val::Px(100.0),
+                            height: Val::Px(100.0),
+                            ..default()
+                        },
+                        HoldRef,
+                        Name::new("Hold"),
+                    ));
+                });
             for (key, command) in vec![
                 ("+", "Increase Volume"),
                 ("-", "Decrease Volume"),
@@ -90,10 +169,15 @@ pub fn sidebar_left(mut commands: Commands, board_assets: Res<BoardAssets>) {
                 ("C", "Clear (disabled)"),
                 ("L", "Load"),
                 ("S", "Save"),
-                ("Arrow Up", "Rotate"),
-                ("Arrow Down", "Rotate"),
+                ("Page Up", "Level Up"),
+                ("Page Down", "Level Down"),
+                ("Arrow Up / X", "Rotate"),
+                ("Ctrl / Z", "Rotate Back"),
+                ("Arrow Down", "Soft Drop"),
+                ("Space", "Hard Drop"),
                 ("Arrow Left", "Move Left"),
                 ("Arrow Right", "Move Right"),
+                ("Shift", "Hold"),
             ]
             .iter()
             {
@@ -1,9 +1,35 @@
 use bevy::prelude::*;
 
-use crate::Tetromino;
+use crate::{ActiveModifiers, BaseColor, BlockState, CurrentTetromino, LockedAt, Tetromino, Wall};
 
-pub(crate) fn update_block_sprites_colors(mut query: Query<(&Tetromino, &mut Sprite)>) {
-    query.iter_mut().for_each(|(t, mut s)| {
-        s.color = t.color;
-    });
+/// Applies the colour modifiers to every block sprite of the map, including garbage and walls.
+/// Walls pass as [`BlockState::Wall`].
+pub(crate) fn update_block_sprites_colors(
+    modifiers: Res<ActiveModifiers>,
+    time: Res<Time>,
+    mut query: Query<(
+        AnyOf<(&Tetromino, &BaseColor)>,
+        &mut Sprite,
+        Has<CurrentTetromino>,
+        Has<Wall>,
+        Option<&LockedAt>,
+    )>,
+) {
+    query
+        .iter_mut()
+        .for_each(|(colors, mut s, current, wall, locked_at)| {
+            let color = match colors {
+                (Some(t), _) => t.color,
+                (None, Some(base)) => base.0,
+                (None, None) => unreachable!("AnyOf matches at least one component"),
+            };
+            let state = match (current, wall, locked_at) {
+                (true, _, _) => BlockState::Active,
+                (false, true, _) => BlockState::Wall,
+                (false, false, locked_at) => {
+                    BlockState::Locked(locked_at.map(|l| time.elapsed().saturating_sub(l.0)))
+                }
+            };
+            s.color = modifiers.block_color(color, state);
+        });
 }
@@ -1,2 +0,0 @@
-mod move_block;
-pub(crate) use move_block::move_block;